@@ -0,0 +1,77 @@
+use crate::{f32_to_u8, u8_to_f32};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum BlendMode {
+    #[default]
+    Normal,
+    Multiply,
+    Screen,
+    Overlay,
+    Darken,
+    Lighten,
+    Difference,
+    Add,
+    SoftLight,
+}
+
+impl BlendMode {
+    // mixes backdrop `cb` with source `cs`, both in 0..1, following the w3c compositing spec
+    pub fn apply(self, cb: f32, cs: f32) -> f32 {
+        match self {
+            BlendMode::Normal => cs,
+            BlendMode::Multiply => cb * cs,
+            BlendMode::Screen => cb + cs - cb * cs,
+            BlendMode::Overlay => {
+                if cb <= 0.5 {
+                    cs * 2.0 * cb
+                } else {
+                    BlendMode::Screen.apply(2.0 * cb - 1.0, cs)
+                }
+            }
+            BlendMode::Darken => cb.min(cs),
+            BlendMode::Lighten => cb.max(cs),
+            BlendMode::Difference => (cb - cs).abs(),
+            BlendMode::Add => (cb + cs).min(1.0),
+            BlendMode::SoftLight => {
+                if cs <= 0.5 {
+                    cb - (1.0 - 2.0 * cs) * cb * (1.0 - cb)
+                } else {
+                    let d = if cb <= 0.25 {
+                        ((16.0 * cb - 12.0) * cb + 4.0) * cb
+                    } else {
+                        cb.sqrt()
+                    };
+                    cb + (2.0 * cs - 1.0) * (d - cb)
+                }
+            }
+        }
+    }
+}
+
+// source-over compositing of straight (non-premultiplied) rgba, channels in 0..1
+pub(crate) fn over(dst: [f32; 4], src: [f32; 4]) -> [f32; 4] {
+    let (sa, da) = (src[3], dst[3]);
+    let out_a = sa + da * (1.0 - sa);
+    if out_a <= 0.0 {
+        return [0.0; 4];
+    }
+    let c = |i: usize| (src[i] * sa + dst[i] * da * (1.0 - sa)) / out_a;
+    [c(0), c(1), c(2), out_a]
+}
+
+pub(crate) fn composite(dst: [f32; 4], src: [f32; 4], mode: BlendMode) -> [f32; 4] {
+    if mode == BlendMode::Normal {
+        return over(dst, src);
+    }
+    let da = dst[3];
+    let mix = |i: usize| (1.0 - da) * src[i] + da * mode.apply(dst[i], src[i]);
+    over(dst, [mix(0), mix(1), mix(2), src[3]])
+}
+
+pub(crate) fn composite_u8(dst: [u8; 4], src: [u8; 4], mode: BlendMode) -> [u8; 4] {
+    match (src[3], mode) {
+        (0, _) => dst,
+        (255, BlendMode::Normal) => src,
+        _ => composite(dst.map(u8_to_f32), src.map(u8_to_f32), mode).map(f32_to_u8),
+    }
+}
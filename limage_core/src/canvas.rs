@@ -0,0 +1,305 @@
+use std::rc::Rc;
+
+use shiterators::{FillRule, StrokeStyle};
+
+use crate::transform::{self, Affine, Sampling};
+use crate::{f32_to_u8, FontHandle, Limage, LimageError, LimageLuma, LimageRgba, TextScale};
+
+// draws into any `Limage` through a transform, so a component can be drawn in its own local
+// coordinates. transforms stack like in html canvas: each call applies in the current local
+// space and `push`/`pop` save and restore it, together with the clip
+pub struct Canvas<'a, L: Limage + ?Sized> {
+    image: &'a mut L,
+    transform: Affine,
+    // per pixel coverage of the image, None lets everything through. shared with the saved states
+    // and only copied when a clip changes it
+    clip: Option<Rc<Vec<u8>>>,
+    saved: Vec<(Affine, Option<Rc<Vec<u8>>>)>,
+}
+
+// segments used when an ellipse has to go through the transform as a polygon
+const ELLIPSE_SEGMENTS: usize = 64;
+
+impl<'a, L: Limage + ?Sized> Canvas<'a, L> {
+    pub fn new(image: &'a mut L) -> Self {
+        Canvas { image, transform: Affine::identity(), clip: None, saved: vec![] }
+    }
+
+    pub fn image(&self) -> &L {
+        self.image
+    }
+    pub fn image_mut(&mut self) -> &mut L {
+        self.image
+    }
+
+    pub fn push(&mut self) {
+        self.saved.push((self.transform, self.clip.clone()));
+    }
+    // restores the transform and clip from the matching `push`, does nothing if there was none
+    pub fn pop(&mut self) {
+        if let Some((transform, clip)) = self.saved.pop() {
+            self.transform = transform;
+            self.clip = clip;
+        }
+    }
+    // drops the transform and the clip, saved states are kept
+    pub fn reset(&mut self) {
+        self.transform = Affine::identity();
+        self.clip = None;
+    }
+
+    pub fn transform(&self) -> Affine {
+        self.transform
+    }
+    pub fn apply(&mut self, local: Affine) {
+        self.transform = local.then(self.transform);
+    }
+    pub fn translate(&mut self, dx: f32, dy: f32) {
+        self.apply(Affine::translate(dx, dy));
+    }
+    pub fn scale(&mut self, sx: f32, sy: f32) {
+        self.apply(Affine::scale(sx, sy));
+    }
+    // radians, clockwise on screen
+    pub fn rotate(&mut self, angle: f32) {
+        self.apply(Affine::rotate(angle));
+    }
+
+    fn map(&self, p: (f32, f32)) -> (f32, f32) {
+        self.transform.apply(p)
+    }
+    fn map_round(&self, p: (f32, f32)) -> (i32, i32) {
+        let (x, y) = self.map(p);
+        (x.round() as i32, y.round() as i32)
+    }
+    // how much the transform grows lengths on average, for stroke widths
+    fn length_scale(&self) -> f32 {
+        let [[a, b, _], [d, e, _]] = self.transform.m;
+        (a * e - b * d).abs().sqrt()
+    }
+    fn scaled_style(&self, style: StrokeStyle) -> StrokeStyle {
+        StrokeStyle { width: style.width * self.length_scale(), ..style }
+    }
+
+    fn index(&self, p: (i32, i32)) -> usize {
+        (p.1 * self.image.width() as i32 + p.0) as usize
+    }
+
+    // every pixel drawn through the canvas ends up here
+    fn blend(&mut self, p: (i32, i32), color: [u8; 4]) {
+        if !self.image.in_bounds(p) {
+            return;
+        }
+        let a = match &self.clip {
+            Some(clip) => (color[3] as u16 * clip[self.index(p)] as u16 / 255) as u8,
+            None => color[3],
+        };
+        if a > 0 {
+            self.image.blend_rgba(p, [color[0], color[1], color[2], a]);
+        }
+    }
+    fn fill<I: IntoIterator<Item = (i32, i32)>>(&mut self, shape: I, color: [u8; 4]) {
+        for p in shape {
+            self.blend(p, color);
+        }
+    }
+    fn fill_aa<I: IntoIterator<Item = ((i32, i32), f32)>>(&mut self, shape: I, color: [u8; 4]) {
+        for (p, coverage) in shape {
+            let a = (color[3] as f32 * coverage.clamp(0.0, 1.0)).round() as u8;
+            self.blend(p, [color[0], color[1], color[2], a]);
+        }
+    }
+
+    fn intersect_clip(&mut self, coverage: Vec<u8>) {
+        match &mut self.clip {
+            Some(clip) => {
+                let clip = Rc::make_mut(clip);
+                clip.iter_mut().zip(coverage).for_each(|(c, n)| *c = (*c as u16 * n as u16 / 255) as u8);
+            }
+            None => self.clip = Some(Rc::new(coverage)),
+        }
+    }
+    // clips go through the current transform and only ever shrink the visible area, `pop` undoes them
+    pub fn clip_polygon(&mut self, points: &[(f32, f32)], rule: FillRule) -> Result<(), LimageError> {
+        let contour: Vec<_> = points.iter().map(|&p| self.map(p)).collect();
+        let polygon = shiterators::Polygon::from_contours_f32(&[contour], rule)?;
+        let mut coverage = vec![0; (self.image.width() * self.image.height()) as usize];
+        for p in polygon {
+            if self.image.in_bounds(p) {
+                coverage[self.index(p)] = 255;
+            }
+        }
+        self.intersect_clip(coverage);
+        Ok(())
+    }
+    // inclusive corners like `fill_rectangle`
+    pub fn clip_rectangle(&mut self, p1: (f32, f32), p2: (f32, f32)) {
+        let (x1, x2) = (p1.0.min(p2.0) - 0.5, p1.0.max(p2.0) + 0.5);
+        let (y1, y2) = (p1.1.min(p2.1) - 0.5, p1.1.max(p2.1) + 0.5);
+        let _ = self.clip_polygon(&[(x1, y1), (x2, y1), (x2, y2), (x1, y2)], FillRule::NonZero);
+    }
+    // soft clip, 255 in `mask` shows everything and 0 nothing. `position` places the mask's top left
+    // pixel in local coordinates and everything outside the mask gets hidden
+    pub fn clip_mask(&mut self, mask: &LimageLuma, position: (f32, f32)) {
+        let (w, h) = (self.image.width(), self.image.height());
+        let matrix = Affine::translate(position.0, position.1).then(self.transform);
+        let coverage = match matrix.inverse() {
+            Some(inverse) => (0..h as i32)
+                .flat_map(|y| (0..w as i32).map(move |x| (x, y)))
+                .map(|(x, y)| {
+                    let [l, _, _, a] = transform::sample(mask, inverse.apply((x as f32, y as f32)), Sampling::Bilinear);
+                    f32_to_u8(l * a)
+                })
+                .collect(),
+            None => vec![0; (w * h) as usize],
+        };
+        self.intersect_clip(coverage);
+    }
+
+    // runs any `Limage` drawing on the image, like `draw_pie`, `paste_with` or `write_text_block`,
+    // and keeps only what lands inside the clip. it works in image coordinates, the transform
+    // doesn't apply
+    pub fn clipped<F: FnOnce(&mut L)>(&mut self, draw: F) {
+        let clip = match self.clip.clone() {
+            Some(clip) => clip,
+            None => return draw(self.image),
+        };
+        let w = self.image.width() as i32;
+        let position = |i: usize| (i as i32 % w, i as i32 / w);
+        let before: Vec<_> = (0..clip.len()).map(|i| self.image.get_rgba_f32(position(i)).unwrap_or([0.0; 4])).collect();
+        draw(self.image);
+        for (i, (&coverage, old)) in clip.iter().zip(before).enumerate() {
+            if coverage == 255 {
+                continue;
+            }
+            let p = position(i);
+            if let Some(new) = self.image.get_rgba_f32(p) {
+                self.image.put_rgba_f32(p, mix(old, new, coverage as f32 / 255.0));
+            }
+        }
+    }
+
+    pub fn line(&mut self, p1: (f32, f32), p2: (f32, f32), color: [u8; 4]) {
+        let line = shiterators::Line::new(self.map_round(p1), self.map_round(p2));
+        self.fill(line, color);
+    }
+    pub fn line_aa(&mut self, p1: (f32, f32), p2: (f32, f32), color: [u8; 4]) {
+        let line = shiterators::LineAA::new(self.map(p1), self.map(p2));
+        self.fill_aa(line, color);
+    }
+    // the stroke width scales with the transform
+    pub fn line_styled(&mut self, p1: (f32, f32), p2: (f32, f32), style: StrokeStyle, color: [u8; 4]) -> Result<(), LimageError> {
+        let stroke = shiterators::Stroke::line(self.map_round(p1), self.map_round(p2), self.scaled_style(style))?;
+        self.fill(stroke, color);
+        Ok(())
+    }
+    pub fn path(&mut self, points: &[(f32, f32)], style: StrokeStyle, color: [u8; 4]) -> Result<(), LimageError> {
+        let points: Vec<_> = points.iter().map(|&p| self.map_round(p)).collect();
+        let stroke = shiterators::Stroke::path(&points, self.scaled_style(style))?;
+        self.fill(stroke, color);
+        Ok(())
+    }
+    pub fn circuit(&mut self, points: &[(f32, f32)], style: StrokeStyle, color: [u8; 4]) -> Result<(), LimageError> {
+        let points: Vec<_> = points.iter().map(|&p| self.map_round(p)).collect();
+        let stroke = shiterators::Stroke::circuit(&points, self.scaled_style(style))?;
+        self.fill(stroke, color);
+        Ok(())
+    }
+
+    pub fn fill_polygon(&mut self, points: &[(f32, f32)], rule: FillRule, color: [u8; 4]) -> Result<(), LimageError> {
+        let contour: Vec<_> = points.iter().map(|&p| self.map(p)).collect();
+        let polygon = shiterators::Polygon::from_contours_f32(&[contour], rule)?;
+        self.fill(polygon, color);
+        Ok(())
+    }
+    // inclusive corners like `Limage::draw_rectangle`
+    pub fn fill_rectangle(&mut self, p1: (f32, f32), p2: (f32, f32), color: [u8; 4]) {
+        let (x1, x2) = (p1.0.min(p2.0) - 0.5, p1.0.max(p2.0) + 0.5);
+        let (y1, y2) = (p1.1.min(p2.1) - 0.5, p1.1.max(p2.1) + 0.5);
+        // four distinct corners always make a valid polygon
+        let _ = self.fill_polygon(&[(x1, y1), (x2, y1), (x2, y2), (x1, y2)], FillRule::NonZero, color);
+    }
+    pub fn fill_ellipse(&mut self, center: (f32, f32), radii: (f32, f32), color: [u8; 4]) {
+        let points: Vec<_> = (0..ELLIPSE_SEGMENTS)
+            .map(|i| {
+                let t = i as f32 / ELLIPSE_SEGMENTS as f32 * std::f32::consts::TAU;
+                (center.0 + radii.0 * t.cos(), center.1 + radii.1 * t.sin())
+            })
+            .collect();
+        let _ = self.fill_polygon(&points, FillRule::NonZero, color);
+    }
+
+    // draws `other` with its top left pixel at `position` in local coordinates
+    pub fn draw_image<M: Limage + ?Sized>(&mut self, other: &M, position: (f32, f32)) {
+        self.draw_image_through(other, Affine::translate(position.0, position.1).then(self.transform));
+    }
+    // `matrix` maps `other`'s pixels straight onto the image
+    fn draw_image_through<M: Limage + ?Sized>(&mut self, other: &M, matrix: Affine) {
+        let (w, h) = (other.width() as f32, other.height() as f32);
+        if w == 0.0 || h == 0.0 {
+            return;
+        }
+        let inverse = match matrix.inverse() {
+            Some(inverse) => inverse,
+            None => return,
+        };
+        // pixel edges rather than centers, so the box covers the whole image
+        let corners = [(-0.5, -0.5), (w - 0.5, -0.5), (-0.5, h - 0.5), (w - 0.5, h - 0.5)].map(|p| matrix.apply(p));
+        let (x1, x2) = corners.iter().fold((f32::MAX, f32::MIN), |(lo, hi), c| (lo.min(c.0), hi.max(c.0)));
+        let (y1, y2) = corners.iter().fold((f32::MAX, f32::MIN), |(lo, hi), c| (lo.min(c.1), hi.max(c.1)));
+        let (x1, x2) = ((x1.floor() as i32).max(0), (x2.ceil() as i32).min(self.image.width() as i32 - 1));
+        let (y1, y2) = ((y1.floor() as i32).max(0), (y2.ceil() as i32).min(self.image.height() as i32 - 1));
+        for y in y1..=y2 {
+            for x in x1..=x2 {
+                let color = transform::sample(other, inverse.apply((x as f32, y as f32)), Sampling::Bilinear);
+                self.blend((x, y), color.map(f32_to_u8));
+            }
+        }
+    }
+
+    // `position` is the top left of the line box in local coordinates
+    pub fn text(&mut self, position: (f32, f32), color: [u8; 4], text: &str, scale: impl Into<TextScale>, font: &FontHandle) {
+        let scale = scale.into();
+        let ascent = font.line_metrics(scale).ascent;
+        self.text_baseline((position.0, position.1 + ascent), color, text, scale, font);
+    }
+    // `origin` is the left end of the baseline in local coordinates
+    pub fn text_baseline(&mut self, origin: (f32, f32), color: [u8; 4], text: &str, scale: impl Into<TextScale>, font: &FontHandle) {
+        // the transform's scale goes into the glyph size so the text is rasterized sharp at the
+        // size it ends up, only rotation and shear are left for resampling
+        let [[a, b, _], [d, e, _]] = self.transform.m;
+        let (sx, sy) = ((a * a + d * d).sqrt(), (b * b + e * e).sqrt());
+        if sx <= 0.0 || sy <= 0.0 {
+            return;
+        }
+        let scale = scale.into();
+        let scale = TextScale::new(scale.x * sx, scale.y * sy);
+        // only the ink gets rendered, glyphs may reach past the line box
+        let ((x1, y1), (x2, y2)) = match font.measure(text, scale).bounds {
+            Some(bounds) => bounds,
+            None => return,
+        };
+        // the baseline keeps the fraction of a pixel it lands on, so without rotation the rendered
+        // pixels line up with the image's. that can move glyph edges, hence a pixel of margin
+        let (ox, oy) = self.map(origin);
+        let offset = ((1 - x1) as f32 + ox - ox.floor(), (1 - y1) as f32 + oy - oy.floor());
+        let mut rendered = LimageRgba::new((x2 - x1 + 3) as u32, (y2 - y1 + 3) as u32);
+        rendered.write_text_baseline(offset, color, text, scale, font);
+        let rest = Affine::new([[a / sx, b / sy, 0.0], [d / sx, e / sy, 0.0]]);
+        let matrix = Affine::translate(-offset.0, -offset.1).then(rest).then(Affine::translate(ox, oy));
+        self.draw_image_through(&rendered, matrix);
+    }
+}
+
+// `t` of the way from `a` to `b`, premultiplied so a transparent side doesn't tint the other
+fn mix(a: [f32; 4], b: [f32; 4], t: f32) -> [f32; 4] {
+    let alpha = a[3] + (b[3] - a[3]) * t;
+    if alpha <= 0.0 {
+        return [0.0; 4];
+    }
+    let mut out = [0.0, 0.0, 0.0, alpha];
+    for i in 0..3 {
+        out[i] = (a[i] * a[3] + (b[i] * b[3] - a[i] * a[3]) * t) / alpha;
+    }
+    out
+}
@@ -0,0 +1,308 @@
+use crate::{f32_to_u8, u8_to_f32};
+
+// color space math on normalized channels, everything in 0..1 except hue which is in degrees
+
+pub(crate) fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+pub(crate) fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+pub(crate) fn rgb_to_hsl(rgb: [f32; 3]) -> [f32; 3] {
+    let [r, g, b] = rgb;
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let l = (max + min) / 2.0;
+    let d = max - min;
+    if d == 0.0 {
+        return [0.0, 0.0, l];
+    }
+    let s = d / (1.0 - (2.0 * l - 1.0).abs());
+    let h = match max {
+        _ if max == r => 60.0 * ((g - b) / d).rem_euclid(6.0),
+        _ if max == g => 60.0 * ((b - r) / d + 2.0),
+        _ => 60.0 * ((r - g) / d + 4.0),
+    };
+    [h, s, l]
+}
+
+pub(crate) fn hsl_to_rgb(hsl: [f32; 3]) -> [f32; 3] {
+    let h = hsl[0].rem_euclid(360.0);
+    let (s, l) = (hsl[1], hsl[2]);
+
+    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    let x = c * (1.0 - ((h / 60.0) % 2.0 - 1.0).abs());
+    let m = l - c / 2.0;
+
+    let (r, g, b) = match h {
+        _ if h < 60.0 => (c, x, 0.0),
+        _ if h < 120.0 => (x, c, 0.0),
+        _ if h < 180.0 => (0.0, c, x),
+        _ if h < 240.0 => (0.0, x, c),
+        _ if h < 300.0 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    [r + m, g + m, b + m]
+}
+
+// https://bottosson.github.io/posts/oklab/
+// computed in f64, the published matrices carry more digits than f32 holds
+pub(crate) fn linear_to_oklab(rgb: [f32; 3]) -> [f32; 3] {
+    let [r, g, b] = rgb.map(|c| c as f64);
+    let l = (0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b).cbrt();
+    let m = (0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b).cbrt();
+    let s = (0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b).cbrt();
+    [
+        0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+        1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+        0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+    ]
+    .map(|c| c as f32)
+}
+
+pub(crate) fn oklab_to_linear(lab: [f32; 3]) -> [f32; 3] {
+    let [l, a, b] = lab.map(|c| c as f64);
+    let l_ = (l + 0.3963377774 * a + 0.2158037573 * b).powi(3);
+    let m_ = (l - 0.1055613458 * a - 0.0638541728 * b).powi(3);
+    let s_ = (l - 0.0894841775 * a - 1.2914855480 * b).powi(3);
+    [
+        4.0767416621 * l_ - 3.3077115913 * m_ + 0.2309699292 * s_,
+        -1.2684380046 * l_ + 2.6097574011 * m_ - 0.3413193965 * s_,
+        -0.0041960863 * l_ - 0.7034186147 * m_ + 1.7076147010 * s_,
+    ]
+    .map(|c| c as f32)
+}
+
+pub(crate) fn rgb_to_hsv(rgb: [f32; 3]) -> [f32; 3] {
+    let [r, g, b] = rgb;
+    let max = r.max(g).max(b);
+    let d = max - r.min(g).min(b);
+    if d == 0.0 {
+        return [0.0, 0.0, max];
+    }
+    let h = match max {
+        _ if max == r => 60.0 * ((g - b) / d).rem_euclid(6.0),
+        _ if max == g => 60.0 * ((b - r) / d + 2.0),
+        _ => 60.0 * ((r - g) / d + 4.0),
+    };
+    [h, d / max, max]
+}
+
+pub(crate) fn hsv_to_rgb(hsv: [f32; 3]) -> [f32; 3] {
+    let [h, s, v] = hsv;
+    let l = v * (1.0 - s / 2.0);
+    let sl = if l == 0.0 || l == 1.0 { 0.0 } else { (v - l) / l.min(1.0 - l) };
+    hsl_to_rgb([h, sl, l])
+}
+
+// cie lab relative to the d65 white point that srgb uses
+const WHITE: [f64; 3] = [0.95047, 1.0, 1.08883];
+const EPSILON: f64 = 216.0 / 24389.0;
+const KAPPA: f64 = 24389.0 / 27.0;
+
+pub(crate) fn linear_to_lab(rgb: [f32; 3]) -> [f32; 3] {
+    let [r, g, b] = rgb.map(|c| c as f64);
+    let xyz = [
+        0.4124564 * r + 0.3575761 * g + 0.1804375 * b,
+        0.2126729 * r + 0.7151522 * g + 0.0721750 * b,
+        0.0193339 * r + 0.1191920 * g + 0.9503041 * b,
+    ];
+    let f = |i: usize| {
+        let t = xyz[i] / WHITE[i];
+        if t > EPSILON {
+            t.cbrt()
+        } else {
+            (KAPPA * t + 16.0) / 116.0
+        }
+    };
+    let (fx, fy, fz) = (f(0), f(1), f(2));
+    [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)].map(|c| c as f32)
+}
+
+pub(crate) fn lab_to_linear(lab: [f32; 3]) -> [f32; 3] {
+    let [l, a, b] = lab.map(|c| c as f64);
+    let fy = (l + 16.0) / 116.0;
+    let f = [fy + a / 500.0, fy, fy - b / 200.0];
+    let [x, y, z] = [0, 1, 2].map(|i| {
+        let t = if f[i].powi(3) > EPSILON { f[i].powi(3) } else { (116.0 * f[i] - 16.0) / KAPPA };
+        t * WHITE[i]
+    });
+    [
+        3.2404542 * x - 1.5371385 * y - 0.4985314 * z,
+        -0.9692660 * x + 1.8760108 * y + 0.0415560 * z,
+        0.0556434 * x - 0.2040259 * y + 1.0572252 * z,
+    ]
+    .map(|c| c as f32)
+}
+
+// srgb color with straight alpha, every channel in 0..1. kept as floats so converting to
+// another color space and back doesn't lose anything to 8-bit rounding
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl Color {
+    pub fn new(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Color { r, g, b, a }
+    }
+    pub fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self::rgba(r, g, b, 255)
+    }
+    pub fn rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        let [r, g, b, a] = [r, g, b, a].map(u8_to_f32);
+        Color { r, g, b, a }
+    }
+    pub fn with_alpha(mut self, a: f32) -> Self {
+        self.a = a;
+        self
+    }
+
+    pub fn to_rgb(self) -> [u8; 3] {
+        [self.r, self.g, self.b].map(f32_to_u8)
+    }
+    pub fn to_rgba(self) -> [u8; 4] {
+        [self.r, self.g, self.b, self.a].map(f32_to_u8)
+    }
+
+    fn channels(self) -> [f32; 3] {
+        [self.r, self.g, self.b]
+    }
+    fn from_channels(rgb: [f32; 3]) -> Self {
+        Color { r: rgb[0], g: rgb[1], b: rgb[2], a: 1.0 }
+    }
+
+    pub fn from_linear_rgb(rgb: [f32; 3]) -> Self {
+        Self::from_channels(rgb.map(linear_to_srgb))
+    }
+    pub fn to_linear_rgb(self) -> [f32; 3] {
+        self.channels().map(srgb_to_linear)
+    }
+
+    // hue in degrees, saturation and lightness in 0..1
+    pub fn from_hsl(h: f32, s: f32, l: f32) -> Self {
+        Self::from_channels(hsl_to_rgb([h, s, l]))
+    }
+    pub fn to_hsl(self) -> [f32; 3] {
+        rgb_to_hsl(self.channels())
+    }
+
+    pub fn from_hsv(h: f32, s: f32, v: f32) -> Self {
+        Self::from_channels(hsv_to_rgb([h, s, v]))
+    }
+    pub fn to_hsv(self) -> [f32; 3] {
+        rgb_to_hsv(self.channels())
+    }
+
+    // cie L*a*b* with L in 0..100
+    pub fn from_lab(l: f32, a: f32, b: f32) -> Self {
+        Self::from_linear_rgb(lab_to_linear([l, a, b]))
+    }
+    pub fn to_lab(self) -> [f32; 3] {
+        linear_to_lab(self.to_linear_rgb())
+    }
+
+    pub fn from_oklab(l: f32, a: f32, b: f32) -> Self {
+        Self::from_linear_rgb(oklab_to_linear([l, a, b]))
+    }
+    pub fn to_oklab(self) -> [f32; 3] {
+        linear_to_oklab(self.to_linear_rgb())
+    }
+
+    // polar oklab, hue in degrees
+    pub fn from_oklch(l: f32, c: f32, h: f32) -> Self {
+        let h = h.to_radians();
+        Self::from_oklab(l, c * h.cos(), c * h.sin())
+    }
+    pub fn to_oklch(self) -> [f32; 3] {
+        let [l, a, b] = self.to_oklab();
+        [l, (a * a + b * b).sqrt(), b.atan2(a).to_degrees().rem_euclid(360.0)]
+    }
+
+    // cie76, plain euclidean distance in lab. around 2.3 is the smallest difference people notice
+    pub fn delta_e(self, other: Color) -> f32 {
+        let (p, q) = (self.to_lab(), other.to_lab());
+        (0..3).map(|i| (p[i] - q[i]).powi(2)).sum::<f32>().sqrt()
+    }
+
+    // ciede2000, tracks perceived difference much better than `delta_e` for saturated colors
+    pub fn delta_e_2000(self, other: Color) -> f32 {
+        let [l1, a1, b1] = self.to_lab().map(|c| c as f64);
+        let [l2, a2, b2] = other.to_lab().map(|c| c as f64);
+        let pow25_7 = 25f64.powi(7);
+
+        let c_bar = ((a1 * a1 + b1 * b1).sqrt() + (a2 * a2 + b2 * b2).sqrt()) / 2.0;
+        let g = 0.5 * (1.0 - (c_bar.powi(7) / (c_bar.powi(7) + pow25_7)).sqrt());
+        let (a1, a2) = ((1.0 + g) * a1, (1.0 + g) * a2);
+        let (c1, c2) = ((a1 * a1 + b1 * b1).sqrt(), (a2 * a2 + b2 * b2).sqrt());
+        let hue = |b: f64, a: f64| if a == 0.0 && b == 0.0 { 0.0 } else { b.atan2(a).to_degrees().rem_euclid(360.0) };
+        let (h1, h2) = (hue(b1, a1), hue(b2, a2));
+
+        let dl = l2 - l1;
+        let dc = c2 - c1;
+        let dh = match h2 - h1 {
+            _ if c1 * c2 == 0.0 => 0.0,
+            d if d > 180.0 => d - 360.0,
+            d if d < -180.0 => d + 360.0,
+            d => d,
+        };
+        let dh = 2.0 * (c1 * c2).sqrt() * (dh.to_radians() / 2.0).sin();
+
+        let l_bar = (l1 + l2) / 2.0;
+        let c_bar = (c1 + c2) / 2.0;
+        let h_bar = match (h1, h2) {
+            _ if c1 * c2 == 0.0 => h1 + h2,
+            _ if (h1 - h2).abs() <= 180.0 => (h1 + h2) / 2.0,
+            _ if h1 + h2 < 360.0 => (h1 + h2 + 360.0) / 2.0,
+            _ => (h1 + h2 - 360.0) / 2.0,
+        };
+        let cos = |deg: f64| deg.to_radians().cos();
+        let t = 1.0 - 0.17 * cos(h_bar - 30.0) + 0.24 * cos(2.0 * h_bar) + 0.32 * cos(3.0 * h_bar + 6.0) - 0.20 * cos(4.0 * h_bar - 63.0);
+        let d_theta = 30.0 * (-((h_bar - 275.0) / 25.0).powi(2)).exp();
+        let rc = 2.0 * (c_bar.powi(7) / (c_bar.powi(7) + pow25_7)).sqrt();
+        let sl = 1.0 + 0.015 * (l_bar - 50.0).powi(2) / (20.0 + (l_bar - 50.0).powi(2)).sqrt();
+        let sc = 1.0 + 0.045 * c_bar;
+        let sh = 1.0 + 0.015 * c_bar * t;
+        let rt = -(2.0 * d_theta).to_radians().sin() * rc;
+
+        let (l, c, h) = (dl / sl, dc / sc, dh / sh);
+        (l * l + c * c + h * h + rt * c * h).sqrt() as f32
+    }
+}
+
+impl From<[u8; 3]> for Color {
+    fn from(rgb: [u8; 3]) -> Self {
+        Color::rgb(rgb[0], rgb[1], rgb[2])
+    }
+}
+
+impl From<[u8; 4]> for Color {
+    fn from(rgba: [u8; 4]) -> Self {
+        Color::rgba(rgba[0], rgba[1], rgba[2], rgba[3])
+    }
+}
+
+impl From<Color> for [u8; 3] {
+    fn from(color: Color) -> Self {
+        color.to_rgb()
+    }
+}
+
+impl From<Color> for [u8; 4] {
+    fn from(color: Color) -> Self {
+        color.to_rgba()
+    }
+}
@@ -0,0 +1,282 @@
+use std::str::FromStr;
+
+use crate::{Color, LimageError};
+
+macro_rules! named_colors {
+    ($($konst:ident $name:literal [$r:literal, $g:literal, $b:literal],)*) => {
+        $(pub const $konst: [u8; 3] = [$r, $g, $b];)*
+
+        // every css named color, gray and grey spellings included
+        pub const NAMED_COLORS: &[(&str, [u8; 3])] = &[$(($name, $konst)),*];
+    };
+}
+
+named_colors! {
+    ALICE_BLUE "aliceblue" [240, 248, 255],
+    ANTIQUE_WHITE "antiquewhite" [250, 235, 215],
+    AQUA "aqua" [0, 255, 255],
+    AQUAMARINE "aquamarine" [127, 255, 212],
+    AZURE "azure" [240, 255, 255],
+    BEIGE "beige" [245, 245, 220],
+    BISQUE "bisque" [255, 228, 196],
+    BLACK "black" [0, 0, 0],
+    BLANCHED_ALMOND "blanchedalmond" [255, 235, 205],
+    BLUE "blue" [0, 0, 255],
+    BLUE_VIOLET "blueviolet" [138, 43, 226],
+    BROWN "brown" [165, 42, 42],
+    BURLYWOOD "burlywood" [222, 184, 135],
+    CADET_BLUE "cadetblue" [95, 158, 160],
+    CHARTREUSE "chartreuse" [127, 255, 0],
+    CHOCOLATE "chocolate" [210, 105, 30],
+    CORAL "coral" [255, 127, 80],
+    CORNFLOWER_BLUE "cornflowerblue" [100, 149, 237],
+    CORNSILK "cornsilk" [255, 248, 220],
+    CRIMSON "crimson" [220, 20, 60],
+    CYAN "cyan" [0, 255, 255],
+    DARK_BLUE "darkblue" [0, 0, 139],
+    DARK_CYAN "darkcyan" [0, 139, 139],
+    DARK_GOLDENROD "darkgoldenrod" [184, 134, 11],
+    DARK_GRAY "darkgray" [169, 169, 169],
+    DARK_GREEN "darkgreen" [0, 100, 0],
+    DARK_GREY "darkgrey" [169, 169, 169],
+    DARK_KHAKI "darkkhaki" [189, 183, 107],
+    DARK_MAGENTA "darkmagenta" [139, 0, 139],
+    DARK_OLIVE_GREEN "darkolivegreen" [85, 107, 47],
+    DARK_ORANGE "darkorange" [255, 140, 0],
+    DARK_ORCHID "darkorchid" [153, 50, 204],
+    DARK_RED "darkred" [139, 0, 0],
+    DARK_SALMON "darksalmon" [233, 150, 122],
+    DARK_SEA_GREEN "darkseagreen" [143, 188, 143],
+    DARK_SLATE_BLUE "darkslateblue" [72, 61, 139],
+    DARK_SLATE_GRAY "darkslategray" [47, 79, 79],
+    DARK_SLATE_GREY "darkslategrey" [47, 79, 79],
+    DARK_TURQUOISE "darkturquoise" [0, 206, 209],
+    DARK_VIOLET "darkviolet" [148, 0, 211],
+    DEEP_PINK "deeppink" [255, 20, 147],
+    DEEP_SKY_BLUE "deepskyblue" [0, 191, 255],
+    DIM_GRAY "dimgray" [105, 105, 105],
+    DIM_GREY "dimgrey" [105, 105, 105],
+    DODGER_BLUE "dodgerblue" [30, 144, 255],
+    FIREBRICK "firebrick" [178, 34, 34],
+    FLORAL_WHITE "floralwhite" [255, 250, 240],
+    FOREST_GREEN "forestgreen" [34, 139, 34],
+    FUCHSIA "fuchsia" [255, 0, 255],
+    GAINSBORO "gainsboro" [220, 220, 220],
+    GHOST_WHITE "ghostwhite" [248, 248, 255],
+    GOLD "gold" [255, 215, 0],
+    GOLDENROD "goldenrod" [218, 165, 32],
+    GRAY "gray" [128, 128, 128],
+    GREEN "green" [0, 128, 0],
+    GREEN_YELLOW "greenyellow" [173, 255, 47],
+    GREY "grey" [128, 128, 128],
+    HONEYDEW "honeydew" [240, 255, 240],
+    HOT_PINK "hotpink" [255, 105, 180],
+    INDIAN_RED "indianred" [205, 92, 92],
+    INDIGO "indigo" [75, 0, 130],
+    IVORY "ivory" [255, 255, 240],
+    KHAKI "khaki" [240, 230, 140],
+    LAVENDER "lavender" [230, 230, 250],
+    LAVENDER_BLUSH "lavenderblush" [255, 240, 245],
+    LAWN_GREEN "lawngreen" [124, 252, 0],
+    LEMON_CHIFFON "lemonchiffon" [255, 250, 205],
+    LIGHT_BLUE "lightblue" [173, 216, 230],
+    LIGHT_CORAL "lightcoral" [240, 128, 128],
+    LIGHT_CYAN "lightcyan" [224, 255, 255],
+    LIGHT_GOLDENROD_YELLOW "lightgoldenrodyellow" [250, 250, 210],
+    LIGHT_GRAY "lightgray" [211, 211, 211],
+    LIGHT_GREEN "lightgreen" [144, 238, 144],
+    LIGHT_GREY "lightgrey" [211, 211, 211],
+    LIGHT_PINK "lightpink" [255, 182, 193],
+    LIGHT_SALMON "lightsalmon" [255, 160, 122],
+    LIGHT_SEA_GREEN "lightseagreen" [32, 178, 170],
+    LIGHT_SKY_BLUE "lightskyblue" [135, 206, 250],
+    LIGHT_SLATE_GRAY "lightslategray" [119, 136, 153],
+    LIGHT_SLATE_GREY "lightslategrey" [119, 136, 153],
+    LIGHT_STEEL_BLUE "lightsteelblue" [176, 196, 222],
+    LIGHT_YELLOW "lightyellow" [255, 255, 224],
+    LIME "lime" [0, 255, 0],
+    LIME_GREEN "limegreen" [50, 205, 50],
+    LINEN "linen" [250, 240, 230],
+    MAGENTA "magenta" [255, 0, 255],
+    MAROON "maroon" [128, 0, 0],
+    MEDIUM_AQUAMARINE "mediumaquamarine" [102, 205, 170],
+    MEDIUM_BLUE "mediumblue" [0, 0, 205],
+    MEDIUM_ORCHID "mediumorchid" [186, 85, 211],
+    MEDIUM_PURPLE "mediumpurple" [147, 112, 219],
+    MEDIUM_SEA_GREEN "mediumseagreen" [60, 179, 113],
+    MEDIUM_SLATE_BLUE "mediumslateblue" [123, 104, 238],
+    MEDIUM_SPRING_GREEN "mediumspringgreen" [0, 250, 154],
+    MEDIUM_TURQUOISE "mediumturquoise" [72, 209, 204],
+    MEDIUM_VIOLET_RED "mediumvioletred" [199, 21, 133],
+    MIDNIGHT_BLUE "midnightblue" [25, 25, 112],
+    MINT_CREAM "mintcream" [245, 255, 250],
+    MISTY_ROSE "mistyrose" [255, 228, 225],
+    MOCCASIN "moccasin" [255, 228, 181],
+    NAVAJO_WHITE "navajowhite" [255, 222, 173],
+    NAVY "navy" [0, 0, 128],
+    OLD_LACE "oldlace" [253, 245, 230],
+    OLIVE "olive" [128, 128, 0],
+    OLIVE_DRAB "olivedrab" [107, 142, 35],
+    ORANGE "orange" [255, 165, 0],
+    ORANGE_RED "orangered" [255, 69, 0],
+    ORCHID "orchid" [218, 112, 214],
+    PALE_GOLDENROD "palegoldenrod" [238, 232, 170],
+    PALE_GREEN "palegreen" [152, 251, 152],
+    PALE_TURQUOISE "paleturquoise" [175, 238, 238],
+    PALE_VIOLET_RED "palevioletred" [219, 112, 147],
+    PAPAYA_WHIP "papayawhip" [255, 239, 213],
+    PEACH_PUFF "peachpuff" [255, 218, 185],
+    PERU "peru" [205, 133, 63],
+    PINK "pink" [255, 192, 203],
+    PLUM "plum" [221, 160, 221],
+    POWDER_BLUE "powderblue" [176, 224, 230],
+    PURPLE "purple" [128, 0, 128],
+    REBECCA_PURPLE "rebeccapurple" [102, 51, 153],
+    RED "red" [255, 0, 0],
+    ROSY_BROWN "rosybrown" [188, 143, 143],
+    ROYAL_BLUE "royalblue" [65, 105, 225],
+    SADDLE_BROWN "saddlebrown" [139, 69, 19],
+    SALMON "salmon" [250, 128, 114],
+    SANDY_BROWN "sandybrown" [244, 164, 96],
+    SEA_GREEN "seagreen" [46, 139, 87],
+    SEASHELL "seashell" [255, 245, 238],
+    SIENNA "sienna" [160, 82, 45],
+    SILVER "silver" [192, 192, 192],
+    SKY_BLUE "skyblue" [135, 206, 235],
+    SLATE_BLUE "slateblue" [106, 90, 205],
+    SLATE_GRAY "slategray" [112, 128, 144],
+    SLATE_GREY "slategrey" [112, 128, 144],
+    SNOW "snow" [255, 250, 250],
+    SPRING_GREEN "springgreen" [0, 255, 127],
+    STEEL_BLUE "steelblue" [70, 130, 180],
+    TAN "tan" [210, 180, 140],
+    TEAL "teal" [0, 128, 128],
+    THISTLE "thistle" [216, 191, 216],
+    TOMATO "tomato" [255, 99, 71],
+    TURQUOISE "turquoise" [64, 224, 208],
+    VIOLET "violet" [238, 130, 238],
+    WHEAT "wheat" [245, 222, 179],
+    WHITE "white" [255, 255, 255],
+    WHITE_SMOKE "whitesmoke" [245, 245, 245],
+    YELLOW "yellow" [255, 255, 0],
+    YELLOW_GREEN "yellowgreen" [154, 205, 50],
+}
+
+impl Color {
+    // css color syntax: #rgb, #rgba, #rrggbb, #rrggbbaa, rgb(), rgba(), hsl(), hsla(),
+    // "transparent" and the named colors. both the comma and the space separated forms work
+    pub fn parse(s: &str) -> Result<Color, LimageError> {
+        let s = s.trim().to_ascii_lowercase();
+        let invalid = || LimageError::InvalidColor(format!("'{s}' is not a valid color"));
+        if let Some(hex) = s.strip_prefix('#') {
+            return parse_hex(hex).ok_or_else(invalid);
+        }
+        if s == "transparent" {
+            return Ok(Color::new(0.0, 0.0, 0.0, 0.0));
+        }
+        if let Some((func, args)) = s.split_once('(') {
+            let args = args.strip_suffix(')').ok_or_else(invalid)?;
+            let args: Vec<&str> = args
+                .split(|c: char| c == ',' || c == '/' || c.is_whitespace())
+                .filter(|a| !a.is_empty())
+                .collect();
+            return match func.trim() {
+                "rgb" | "rgba" => parse_rgb(&args),
+                "hsl" | "hsla" => parse_hsl(&args),
+                _ => None,
+            }
+            .ok_or_else(invalid);
+        }
+        Color::named(&s).ok_or_else(invalid)
+    }
+
+    pub fn named(name: &str) -> Option<Color> {
+        NAMED_COLORS
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|&(_, rgb)| rgb.into())
+    }
+
+    // closest css named color by ciede2000, alpha is ignored
+    pub fn nearest_named(self) -> &'static str {
+        let color = self.with_alpha(1.0);
+        NAMED_COLORS
+            .iter()
+            .map(|&(name, rgb)| (name, color.delta_e_2000(rgb.into())))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(name, _)| name)
+            .unwrap_or("black")
+    }
+}
+
+impl FromStr for Color {
+    type Err = LimageError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Color::parse(s)
+    }
+}
+
+fn parse_hex(hex: &str) -> Option<Color> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let digits: Vec<u8> = hex.chars().map(|c| c.to_digit(16).unwrap() as u8).collect();
+    let channels: Vec<u8> = match digits.len() {
+        3 | 4 => digits.iter().map(|d| d * 17).collect(),
+        6 | 8 => digits.chunks(2).map(|d| d[0] * 16 + d[1]).collect(),
+        _ => return None,
+    };
+    let a = channels.get(3).copied().unwrap_or(255);
+    Some(Color::rgba(channels[0], channels[1], channels[2], a))
+}
+
+fn number(arg: &str) -> Option<f32> {
+    arg.parse::<f32>().ok().filter(|n| n.is_finite())
+}
+
+// "50%" -> 0.5, a bare number is divided by `scale`
+fn fraction(arg: &str, scale: f32) -> Option<f32> {
+    match arg.strip_suffix('%') {
+        Some(pct) => number(pct).map(|n| n / 100.0),
+        None => number(arg).map(|n| n / scale),
+    }
+}
+
+fn alpha(args: &[&str]) -> Option<f32> {
+    match args.get(3) {
+        Some(a) => fraction(a, 1.0).map(|a| a.clamp(0.0, 1.0)),
+        None => Some(1.0),
+    }
+}
+
+fn parse_rgb(args: &[&str]) -> Option<Color> {
+    if !(3..=4).contains(&args.len()) {
+        return None;
+    }
+    let mut rgb = [0.0; 3];
+    for i in 0..3 {
+        rgb[i] = fraction(args[i], 255.0)?.clamp(0.0, 1.0);
+    }
+    Some(Color::new(rgb[0], rgb[1], rgb[2], alpha(args)?))
+}
+
+fn parse_hsl(args: &[&str]) -> Option<Color> {
+    if !(3..=4).contains(&args.len()) {
+        return None;
+    }
+    let h = args[0];
+    let h = if let Some(deg) = h.strip_suffix("deg") {
+        number(deg)?
+    } else if let Some(rad) = h.strip_suffix("rad") {
+        number(rad)?.to_degrees()
+    } else if let Some(turn) = h.strip_suffix("turn") {
+        number(turn)? * 360.0
+    } else {
+        number(h)?
+    };
+    // css level 4 allows the percent signs to be left out
+    let s = fraction(args[1], 100.0)?.clamp(0.0, 1.0);
+    let l = fraction(args[2], 100.0)?.clamp(0.0, 1.0);
+    Some(Color::from_hsl(h, s, l).with_alpha(alpha(args)?))
+}
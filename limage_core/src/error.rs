@@ -0,0 +1,69 @@
+use std::fmt;
+
+use image::ImageError;
+use shiterators::ShapeError;
+
+#[derive(Debug)]
+pub enum LimageError {
+    Io(std::io::Error),
+    Decode(String),
+    Encode(String),
+    Font(String),
+    Network(String),
+    InvalidGeometry(String),
+    InvalidColor(String),
+}
+
+impl fmt::Display for LimageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LimageError::Io(e) => write!(f, "io error: {e}"),
+            LimageError::Decode(e) => write!(f, "decode error: {e}"),
+            LimageError::Encode(e) => write!(f, "encode error: {e}"),
+            LimageError::Font(e) => write!(f, "font error: {e}"),
+            LimageError::Network(e) => write!(f, "network error: {e}"),
+            LimageError::InvalidGeometry(e) => write!(f, "invalid geometry: {e}"),
+            LimageError::InvalidColor(e) => write!(f, "invalid color: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for LimageError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LimageError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for LimageError {
+    fn from(e: std::io::Error) -> Self {
+        LimageError::Io(e)
+    }
+}
+
+impl From<ImageError> for LimageError {
+    fn from(e: ImageError) -> Self {
+        match e {
+            ImageError::IoError(e) => LimageError::Io(e),
+            ImageError::Encoding(e) => LimageError::Encode(e.to_string()),
+            e => LimageError::Decode(e.to_string()),
+        }
+    }
+}
+
+impl From<ShapeError> for LimageError {
+    fn from(e: ShapeError) -> Self {
+        match e {
+            ShapeError::InvalidGeometry(e) => LimageError::InvalidGeometry(e),
+            ShapeError::Image(e) => e.into(),
+        }
+    }
+}
+
+impl From<reqwest::Error> for LimageError {
+    fn from(e: reqwest::Error) -> Self {
+        LimageError::Network(e.to_string())
+    }
+}
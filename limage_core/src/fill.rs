@@ -0,0 +1,77 @@
+use crate::Limage;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Connectivity {
+    #[default]
+    Four,
+    Eight,
+}
+
+fn distance(a: [u8; 3], b: [u8; 3]) -> f32 {
+    a.iter()
+        .zip(b.iter())
+        .map(|(&a, &b)| (a as f32 - b as f32).powi(2))
+        .sum::<f32>()
+        .sqrt()
+}
+
+// scanline flood fill with an explicit stack so big regions can't overflow the call stack.
+// returns the inclusive bounding box of the filled region, None if nothing got filled
+pub(crate) fn flood_fill<L: Limage + ?Sized>(
+    img: &mut L,
+    seed: (i32, i32),
+    color: [u8; 3],
+    tolerance: f32,
+    connectivity: Connectivity,
+) -> Option<((i32, i32), (i32, i32))> {
+    let target = img.get_rgb(seed)?;
+    let (w, h) = (img.width() as i32, img.height() as i32);
+    let mut visited = vec![false; w as usize * h as usize];
+    let index = |x: i32, y: i32| y as usize * w as usize + x as usize;
+    let matches = |img: &L, visited: &[bool], x: i32, y: i32| {
+        !visited[index(x, y)] && img.get_rgb((x, y)).is_some_and(|c| distance(c, target) <= tolerance)
+    };
+    let reach = match connectivity {
+        Connectivity::Four => 0,
+        Connectivity::Eight => 1,
+    };
+
+    let mut bounds: Option<((i32, i32), (i32, i32))> = None;
+    let mut stack = vec![seed];
+    while let Some((x, y)) = stack.pop() {
+        if !matches(img, &visited, x, y) {
+            continue;
+        }
+        let mut left = x;
+        while left > 0 && matches(img, &visited, left - 1, y) {
+            left -= 1;
+        }
+        let mut right = x;
+        while right < w - 1 && matches(img, &visited, right + 1, y) {
+            right += 1;
+        }
+        for x in left..=right {
+            visited[index(x, y)] = true;
+            img.put_rgb((x, y), color);
+        }
+        bounds = Some(match bounds {
+            Some((min, max)) => ((min.0.min(left), min.1.min(y)), (max.0.max(right), max.1.max(y))),
+            None => ((left, y), (right, y)),
+        });
+
+        for ny in [y - 1, y + 1] {
+            if ny < 0 || ny >= h {
+                continue;
+            }
+            let mut in_run = false;
+            for nx in (left - reach).max(0)..=(right + reach).min(w - 1) {
+                let m = matches(img, &visited, nx, ny);
+                if m && !in_run {
+                    stack.push((nx, ny));
+                }
+                in_run = m;
+            }
+        }
+    }
+    bounds
+}
@@ -0,0 +1,248 @@
+use crate::{u8_to_f32, Limage, LimageError};
+
+// how pixels past the border are sampled
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum EdgeMode {
+    // repeat the border pixel
+    #[default]
+    Clamp,
+    Wrap,
+    Mirror,
+    // everything outside is transparent black
+    Zero,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Weights {
+    Full { width: usize, height: usize, weights: Vec<f32> },
+    Separable { horizontal: Vec<f32>, vertical: Vec<f32> },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Kernel {
+    weights: Weights,
+    // added to every color channel after convolving, in 0..1
+    pub bias: f32,
+}
+
+impl Kernel {
+    // row-major weights, both sides must be odd so the kernel has a center
+    pub fn new(width: u32, height: u32, weights: Vec<f32>) -> Result<Self, LimageError> {
+        let (width, height) = (width as usize, height as usize);
+        if width & 1 == 0 || height & 1 == 0 {
+            return Err(LimageError::InvalidGeometry(format!("kernel sides must be odd, got {width}x{height}")));
+        }
+        if weights.len() != width * height {
+            return Err(LimageError::InvalidGeometry(format!("a {width}x{height} kernel needs {} weights, got {}", width * height, weights.len())));
+        }
+        Ok(Kernel { weights: Weights::Full { width, height, weights }, bias: 0.0 })
+    }
+
+    // the kernel is the outer product of the two, applied as two 1d passes
+    pub fn separable(horizontal: Vec<f32>, vertical: Vec<f32>) -> Result<Self, LimageError> {
+        if horizontal.len() & 1 == 0 || vertical.len() & 1 == 0 {
+            return Err(LimageError::InvalidGeometry(format!(
+                "kernel sides must be odd, got {}x{}",
+                horizontal.len(),
+                vertical.len()
+            )));
+        }
+        Ok(Kernel { weights: Weights::Separable { horizontal, vertical }, bias: 0.0 })
+    }
+
+    pub fn with_bias(mut self, bias: f32) -> Self {
+        self.bias = bias;
+        self
+    }
+
+    pub fn box_blur(radius: u32) -> Self {
+        let taps = vec![1.0 / (2 * radius + 1) as f32; 2 * radius as usize + 1];
+        Kernel { weights: Weights::Separable { horizontal: taps.clone(), vertical: taps }, bias: 0.0 }
+    }
+
+    // covers three standard deviations on each side
+    pub fn gaussian(sigma: f32) -> Self {
+        let radius = (sigma * 3.0).ceil().max(0.0) as i32;
+        let mut taps: Vec<f32> = (-radius..=radius)
+            .map(|i| if sigma > 0.0 { (-(i * i) as f32 / (2.0 * sigma * sigma)).exp() } else { 1.0 })
+            .collect();
+        let sum: f32 = taps.iter().sum();
+        taps.iter_mut().for_each(|t| *t /= sum);
+        Kernel { weights: Weights::Separable { horizontal: taps.clone(), vertical: taps }, bias: 0.0 }
+    }
+
+    pub fn sharpen() -> Self {
+        Self::full3([0.0, -1.0, 0.0, -1.0, 5.0, -1.0, 0.0, -1.0, 0.0])
+    }
+
+    pub fn emboss() -> Self {
+        Self::full3([-2.0, -1.0, 0.0, -1.0, 1.0, 1.0, 0.0, 1.0, 2.0])
+    }
+
+    pub fn sobel_x() -> Self {
+        Kernel { weights: Weights::Separable { horizontal: vec![-1.0, 0.0, 1.0], vertical: vec![1.0, 2.0, 1.0] }, bias: 0.0 }
+    }
+
+    pub fn sobel_y() -> Self {
+        Kernel { weights: Weights::Separable { horizontal: vec![1.0, 2.0, 1.0], vertical: vec![-1.0, 0.0, 1.0] }, bias: 0.0 }
+    }
+
+    pub fn laplacian() -> Self {
+        Self::full3([0.0, 1.0, 0.0, 1.0, -4.0, 1.0, 0.0, 1.0, 0.0])
+    }
+
+    fn full3(weights: [f32; 9]) -> Self {
+        Kernel { weights: Weights::Full { width: 3, height: 3, weights: weights.to_vec() }, bias: 0.0 }
+    }
+
+    pub fn sum(&self) -> f32 {
+        match &self.weights {
+            Weights::Full { weights, .. } => weights.iter().sum(),
+            Weights::Separable { horizontal, vertical } => horizontal.iter().sum::<f32>() * vertical.iter().sum::<f32>(),
+        }
+    }
+}
+
+// premultiplied rgba at the image's own precision, row-major
+struct Buffer {
+    width: i32,
+    height: i32,
+    pixels: Vec<[f32; 4]>,
+}
+
+impl Buffer {
+    fn load<L: Limage + ?Sized>(img: &L) -> Self {
+        let (width, height) = (img.width() as i32, img.height() as i32);
+        let mut pixels = Vec::with_capacity((width * height) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let [r, g, b, a] = img.get_rgba_f32((x, y)).unwrap_or([0.0; 4]);
+                pixels.push([r * a, g * a, b * a, a]);
+            }
+        }
+        Buffer { width, height, pixels }
+    }
+
+    fn sample(&self, x: i32, y: i32, edge: EdgeMode) -> [f32; 4] {
+        let wrap = |v: i32, n: i32| match edge {
+            EdgeMode::Clamp | EdgeMode::Zero => v.clamp(0, n - 1),
+            EdgeMode::Wrap => v.rem_euclid(n),
+            EdgeMode::Mirror => {
+                // reflects without repeating the border pixel: -1 -> 1, n -> n - 2
+                let period = (2 * n - 2).max(1);
+                let m = v.rem_euclid(period);
+                if m < n {
+                    m
+                } else {
+                    period - m
+                }
+            }
+        };
+        if edge == EdgeMode::Zero && (x < 0 || y < 0 || x >= self.width || y >= self.height) {
+            return [0.0; 4];
+        }
+        self.pixels[(wrap(y, self.height) * self.width + wrap(x, self.width)) as usize]
+    }
+
+    fn pass(&self, edge: EdgeMode, width: usize, height: usize, weights: &[f32]) -> Buffer {
+        let (cx, cy) = ((width / 2) as i32, (height / 2) as i32);
+        let mut pixels = Vec::with_capacity(self.pixels.len());
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let mut acc = [0.0; 4];
+                for ky in 0..height {
+                    for kx in 0..width {
+                        let weight = weights[ky * width + kx];
+                        if weight == 0.0 {
+                            continue;
+                        }
+                        let s = self.sample(x + kx as i32 - cx, y + ky as i32 - cy, edge);
+                        for (a, s) in acc.iter_mut().zip(s) {
+                            *a += s * weight;
+                        }
+                    }
+                }
+                pixels.push(acc);
+            }
+        }
+        Buffer { width: self.width, height: self.height, pixels }
+    }
+
+    fn convolve(&self, kernel: &Kernel, edge: EdgeMode) -> Buffer {
+        match &kernel.weights {
+            Weights::Full { width, height, weights } => self.pass(edge, *width, *height, weights),
+            Weights::Separable { horizontal, vertical } => self
+                .pass(edge, horizontal.len(), 1, horizontal)
+                .pass(edge, 1, vertical.len(), vertical),
+        }
+    }
+}
+
+// smoothing and sharpening kernels (weights summing to 1) filter alpha along with the color.
+// anything else, like an edge detector, would wipe out alpha, so it keeps the source alpha and
+// the color result is used as is. clamping is left to `put_rgba_f32`
+fn finish(source: &Buffer, out: &Buffer, kernel: &Kernel) -> Vec<[f32; 4]> {
+    let keeps_alpha = (kernel.sum() - 1.0).abs() > 1e-3;
+    source
+        .pixels
+        .iter()
+        .zip(&out.pixels)
+        .map(|(src, &[r, g, b, a])| {
+            let (rgb, alpha) = if keeps_alpha {
+                ([r, g, b], src[3])
+            } else if a > 0.0 {
+                ([r / a, g / a, b / a], a)
+            } else {
+                ([0.0; 3], 0.0)
+            };
+            let [r, g, b] = rgb.map(|c| c + kernel.bias);
+            [r, g, b, alpha]
+        })
+        .collect()
+}
+
+pub(crate) fn convolve<L: Limage + ?Sized>(img: &L, kernel: &Kernel, edge: EdgeMode) -> Vec<[f32; 4]> {
+    let source = Buffer::load(img);
+    let out = source.convolve(kernel, edge);
+    finish(&source, &out, kernel)
+}
+
+// per channel gradient magnitude
+pub(crate) fn sobel<L: Limage + ?Sized>(img: &L) -> Vec<[f32; 4]> {
+    let source = Buffer::load(img);
+    let gx = source.convolve(&Kernel::sobel_x(), EdgeMode::Clamp);
+    let gy = source.convolve(&Kernel::sobel_y(), EdgeMode::Clamp);
+    source
+        .pixels
+        .iter()
+        .zip(gx.pixels.iter().zip(&gy.pixels))
+        .map(|(src, (x, y))| {
+            let [r, g, b] = [0, 1, 2].map(|i| (x[i] * x[i] + y[i] * y[i]).sqrt());
+            [r, g, b, src[3]]
+        })
+        .collect()
+}
+
+// adds back `amount` times the difference from a gaussian blur wherever it is above `threshold`
+pub(crate) fn unsharp_mask<L: Limage + ?Sized>(img: &L, sigma: f32, amount: f32, threshold: u8) -> Vec<[f32; 4]> {
+    let source = Buffer::load(img);
+    let blurred = source.convolve(&Kernel::gaussian(sigma), EdgeMode::Clamp);
+    let threshold = u8_to_f32(threshold);
+    source
+        .pixels
+        .iter()
+        .zip(&blurred.pixels)
+        .map(|(src, blur)| {
+            let a = src[3];
+            if a == 0.0 {
+                return [0.0; 4];
+            }
+            let [r, g, b] = [0, 1, 2].map(|i| {
+                let diff = src[i] - blur[i];
+                let sharp = if diff.abs() > threshold { src[i] + diff * amount } else { src[i] };
+                sharp / a
+            });
+            [r, g, b, a]
+        })
+        .collect()
+}
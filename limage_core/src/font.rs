@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+
+use rusttype::{point, Font, PositionedGlyph, Scale};
+
+use crate::LimageError;
+
+static TUMS_BASIC: &[u8] = include_bytes!("../../assets/TumsBasic.ttf");
+
+// a parsed font, cheap to clone and share between threads
+#[derive(Clone)]
+pub struct FontHandle {
+    font: Arc<Font<'static>>,
+}
+
+impl FontHandle {
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self, LimageError> {
+        let font = Font::try_from_vec(data).ok_or_else(|| LimageError::Font("not a valid font".to_string()))?;
+        Ok(FontHandle { font: Arc::new(font) })
+    }
+
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, LimageError> {
+        let path = path.as_ref();
+        let data = std::fs::read(path)?;
+        Self::from_bytes(data).map_err(|_| LimageError::Font(format!("'{}' is not a valid font", path.display())))
+    }
+
+    // TumsBasic, built into the binary and only parsed once
+    pub fn embedded() -> Self {
+        static DEFAULT: OnceLock<FontHandle> = OnceLock::new();
+        DEFAULT
+            .get_or_init(|| FontHandle {
+                font: Arc::new(Font::try_from_bytes(TUMS_BASIC).expect("the embedded font is valid")),
+            })
+            .clone()
+    }
+
+    pub fn line_metrics(&self, scale: impl Into<TextScale>) -> LineMetrics {
+        let v = self.font.v_metrics(scale.into().rusttype());
+        LineMetrics { ascent: v.ascent, descent: v.descent, line_gap: v.line_gap }
+    }
+
+    // the extra advance between two characters, usually negative or zero
+    pub fn kerning(&self, first: char, second: char, scale: impl Into<TextScale>) -> f32 {
+        self.font.pair_kerning(scale.into().rusttype(), first, second)
+    }
+
+    // measures a single line of text, positions are relative to the left end of its baseline
+    pub fn measure(&self, text: &str, scale: impl Into<TextScale>) -> TextMetrics {
+        let scale = scale.into();
+        let mut glyphs = vec![];
+        let mut bounds: Option<((i32, i32), (i32, i32))> = None;
+        let mut width = 0.0;
+        for (c, glyph) in text.chars().zip(self.glyphs(text, scale, (0.0, 0.0))) {
+            let advance = glyph.unpositioned().h_metrics().advance_width;
+            let x = glyph.position().x;
+            let ink = glyph.pixel_bounding_box().map(|bb| ((bb.min.x, bb.min.y), (bb.max.x - 1, bb.max.y - 1)));
+            if let Some((min, max)) = ink {
+                bounds = Some(match bounds {
+                    Some((lo, hi)) => ((lo.0.min(min.0), lo.1.min(min.1)), (hi.0.max(max.0), hi.1.max(max.1))),
+                    None => (min, max),
+                });
+            }
+            width = x + advance;
+            glyphs.push(GlyphBox { c, x, advance, bounds: ink });
+        }
+        TextMetrics { width, line: self.line_metrics(scale), glyphs, bounds }
+    }
+
+    // lays the glyphs out along the baseline starting at `origin`, kerning included
+    pub(crate) fn glyphs(&self, text: &str, scale: TextScale, origin: (f32, f32)) -> Vec<PositionedGlyph<'static>> {
+        let scale = scale.rusttype();
+        let mut caret = origin.0;
+        let mut last = None;
+        let mut glyphs = vec![];
+        for c in text.chars() {
+            let glyph = self.font.glyph(c).scaled(scale);
+            if let Some(last) = last {
+                caret += self.font.pair_kerning(scale, last, glyph.id());
+            }
+            last = Some(glyph.id());
+            let advance = glyph.h_metrics().advance_width;
+            glyphs.push(glyph.positioned(point(caret, origin.1)));
+            caret += advance;
+        }
+        glyphs
+    }
+}
+
+// the size of the text in pixels, `y` is the height of a line from descent to ascent and `x`
+// stretches the glyphs horizontally. a plain number is the height with the glyphs twice as wide,
+// the way text has always been drawn here, `uniform` keeps the font's own proportions
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TextScale {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl TextScale {
+    pub fn new(x: f32, y: f32) -> Self {
+        TextScale { x, y }
+    }
+    pub fn uniform(size: f32) -> Self {
+        TextScale { x: size, y: size }
+    }
+    pub fn scaled(self, factor: f32) -> Self {
+        TextScale { x: self.x * factor, y: self.y * factor }
+    }
+    fn rusttype(self) -> Scale {
+        Scale { x: self.x, y: self.y }
+    }
+}
+
+impl From<f32> for TextScale {
+    fn from(size: f32) -> Self {
+        TextScale { x: size * 2.0, y: size }
+    }
+}
+
+// vertical metrics of a line. `ascent` is above the baseline, `descent` below it and so negative
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LineMetrics {
+    pub ascent: f32,
+    pub descent: f32,
+    pub line_gap: f32,
+}
+
+impl LineMetrics {
+    pub fn height(&self) -> f32 {
+        self.ascent - self.descent
+    }
+    // baseline to baseline distance the font asks for
+    pub fn line_advance(&self) -> f32 {
+        self.height() + self.line_gap
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GlyphBox {
+    pub c: char,
+    // where the pen was when the glyph got drawn, after kerning
+    pub x: f32,
+    pub advance: f32,
+    // inclusive pixels covered by the glyph relative to the baseline origin, None for whitespace
+    pub bounds: Option<((i32, i32), (i32, i32))>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextMetrics {
+    // advance width of the whole line, where the next character would go
+    pub width: f32,
+    pub line: LineMetrics,
+    pub glyphs: Vec<GlyphBox>,
+    // union of the glyph boxes, None if nothing visible gets drawn
+    pub bounds: Option<((i32, i32), (i32, i32))>,
+}
+
+impl TextMetrics {
+    // caret x for every character boundary, one more than there are characters
+    pub fn carets(&self) -> Vec<f32> {
+        let mut carets: Vec<f32> = self.glyphs.iter().map(|g| g.x).collect();
+        carets.push(self.width);
+        carets
+    }
+    // the character boundary closest to `x`, for placing a caret under the mouse
+    pub fn index_at(&self, x: f32) -> usize {
+        let carets = self.carets();
+        let mut best = 0;
+        for (i, c) in carets.iter().enumerate() {
+            if (c - x).abs() < (carets[best] - x).abs() {
+                best = i;
+            }
+        }
+        best
+    }
+    // size of the line box, from the top of the ascent to the bottom of the descent
+    pub fn size(&self) -> (f32, f32) {
+        (self.width, self.line.height())
+    }
+}
+
+impl Default for FontHandle {
+    fn default() -> Self {
+        Self::embedded()
+    }
+}
+
+impl std::fmt::Debug for FontHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FontHandle").field("glyphs", &self.font.glyph_count()).finish()
+    }
+}
+
+// loads every font once and hands out handles to it by name
+#[derive(Clone, Debug)]
+pub struct FontRegistry {
+    fonts: HashMap<String, FontHandle>,
+}
+
+impl FontRegistry {
+    // starts out with the embedded font registered as "default"
+    pub fn new() -> Self {
+        let mut fonts = HashMap::new();
+        fonts.insert("default".to_string(), FontHandle::embedded());
+        FontRegistry { fonts }
+    }
+
+    // the path doubles as the name, loading it again returns the cached font
+    pub fn load<P: AsRef<Path>>(&mut self, path: P) -> Result<FontHandle, LimageError> {
+        let name = path.as_ref().to_string_lossy().into_owned();
+        if let Some(handle) = self.fonts.get(&name) {
+            return Ok(handle.clone());
+        }
+        let handle = FontHandle::from_path(path)?;
+        self.fonts.insert(name, handle.clone());
+        Ok(handle)
+    }
+
+    // replaces any font already registered under `name`
+    pub fn load_bytes(&mut self, name: &str, data: Vec<u8>) -> Result<FontHandle, LimageError> {
+        let handle = FontHandle::from_bytes(data)?;
+        self.fonts.insert(name.to_string(), handle.clone());
+        Ok(handle)
+    }
+
+    pub fn insert(&mut self, name: &str, handle: FontHandle) {
+        self.fonts.insert(name.to_string(), handle);
+    }
+
+    pub fn get(&self, name: &str) -> Option<FontHandle> {
+        self.fonts.get(name).cloned()
+    }
+
+    pub fn default_font(&self) -> FontHandle {
+        self.get("default").unwrap_or_default()
+    }
+}
+
+impl Default for FontRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,161 @@
+use std::f32::consts::TAU;
+
+use crate::color::{hsl_to_rgb, linear_to_oklab, linear_to_srgb, oklab_to_linear, rgb_to_hsl, srgb_to_linear};
+use crate::{f32_to_u8, u8_to_f32};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GradientShape {
+    Linear { start: (f32, f32), end: (f32, f32) },
+    Radial { center: (f32, f32), radius: f32 },
+    // angle in radians, same convention as `shiterators::Arc`
+    Conic { center: (f32, f32), angle: f32 },
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Interpolation {
+    #[default]
+    Srgb,
+    LinearRgb,
+    Hsl,
+    Oklab,
+}
+
+// what happens past the first and last stop
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Spread {
+    #[default]
+    Pad,
+    Repeat,
+    Reflect,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gradient {
+    pub shape: GradientShape,
+    pub interpolation: Interpolation,
+    pub spread: Spread,
+    stops: Vec<(f32, [u8; 4])>,
+}
+
+impl Gradient {
+    pub fn new(shape: GradientShape) -> Self {
+        Gradient {
+            shape,
+            interpolation: Interpolation::default(),
+            spread: Spread::default(),
+            stops: vec![],
+        }
+    }
+    pub fn linear(start: (f32, f32), end: (f32, f32)) -> Self {
+        Self::new(GradientShape::Linear { start, end })
+    }
+    pub fn radial(center: (f32, f32), radius: f32) -> Self {
+        Self::new(GradientShape::Radial { center, radius })
+    }
+    pub fn conic(center: (f32, f32), angle: f32) -> Self {
+        Self::new(GradientShape::Conic { center, angle })
+    }
+
+    // `offset` goes from 0 at the start of the gradient to 1 at its end
+    pub fn with_stop(mut self, offset: f32, color: [u8; 4]) -> Self {
+        let i = self.stops.partition_point(|s| s.0 <= offset);
+        self.stops.insert(i, (offset, color));
+        self
+    }
+    pub fn with_interpolation(mut self, interpolation: Interpolation) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+    pub fn with_spread(mut self, spread: Spread) -> Self {
+        self.spread = spread;
+        self
+    }
+
+    pub fn stops(&self) -> &[(f32, [u8; 4])] {
+        &self.stops
+    }
+
+    pub fn color_at(&self, p: (i32, i32)) -> [u8; 4] {
+        let (x, y) = (p.0 as f32, p.1 as f32);
+        let t = match self.shape {
+            GradientShape::Linear { start, end } => {
+                let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+                let len2 = dx * dx + dy * dy;
+                if len2 == 0.0 {
+                    0.0
+                } else {
+                    ((x - start.0) * dx + (y - start.1) * dy) / len2
+                }
+            }
+            GradientShape::Radial { center, radius } => {
+                let d = ((x - center.0).powi(2) + (y - center.1).powi(2)).sqrt();
+                if radius <= 0.0 {
+                    0.0
+                } else {
+                    d / radius
+                }
+            }
+            GradientShape::Conic { center, angle } => ((y - center.1).atan2(x - center.0) - angle).rem_euclid(TAU) / TAU,
+        };
+        self.color_at_offset(t)
+    }
+
+    pub fn color_at_offset(&self, t: f32) -> [u8; 4] {
+        let t = match self.spread {
+            Spread::Pad => t.clamp(0.0, 1.0),
+            Spread::Repeat => t.rem_euclid(1.0),
+            Spread::Reflect => match t.rem_euclid(2.0) {
+                m if m > 1.0 => 2.0 - m,
+                m => m,
+            },
+        };
+        let (first, last) = match (self.stops.first(), self.stops.last()) {
+            (Some(&first), Some(&last)) => (first, last),
+            _ => return [0; 4],
+        };
+        if t <= first.0 {
+            return first.1;
+        }
+        if t >= last.0 {
+            return last.1;
+        }
+        let i = self.stops.partition_point(|s| s.0 <= t);
+        let ((o1, c1), (o2, c2)) = (self.stops[i - 1], self.stops[i]);
+        let local = if o2 > o1 { (t - o1) / (o2 - o1) } else { 0.0 };
+        mix(c1, c2, local, self.interpolation)
+    }
+}
+
+fn mix(c1: [u8; 4], c2: [u8; 4], t: f32, interpolation: Interpolation) -> [u8; 4] {
+    let lerp = |a: f32, b: f32| a + (b - a) * t;
+    let (a, b) = (c1.map(u8_to_f32), c2.map(u8_to_f32));
+    let rgb1 = [a[0], a[1], a[2]];
+    let rgb2 = [b[0], b[1], b[2]];
+    let rgb = match interpolation {
+        Interpolation::Srgb => [0, 1, 2].map(|i| lerp(rgb1[i], rgb2[i])),
+        Interpolation::LinearRgb => {
+            let (l1, l2) = (rgb1.map(srgb_to_linear), rgb2.map(srgb_to_linear));
+            [0, 1, 2].map(|i| linear_to_srgb(lerp(l1[i], l2[i])))
+        }
+        Interpolation::Hsl => {
+            let (mut h1, mut h2) = (rgb_to_hsl(rgb1), rgb_to_hsl(rgb2));
+            // grays have no hue, borrow the other side's so they don't swing through the wheel
+            if h1[1] == 0.0 {
+                h1[0] = h2[0];
+            }
+            if h2[1] == 0.0 {
+                h2[0] = h1[0];
+            }
+            let dh = (h2[0] - h1[0] + 180.0).rem_euclid(360.0) - 180.0;
+            hsl_to_rgb([h1[0] + dh * t, lerp(h1[1], h2[1]), lerp(h1[2], h2[2])])
+        }
+        Interpolation::Oklab => {
+            let l1 = linear_to_oklab(rgb1.map(srgb_to_linear));
+            let l2 = linear_to_oklab(rgb2.map(srgb_to_linear));
+            oklab_to_linear([0, 1, 2].map(|i| lerp(l1[i], l2[i]))).map(|c| linear_to_srgb(c.max(0.0)))
+        }
+    };
+    let alpha = f32_to_u8(lerp(a[3], b[3]));
+    let [r, g, b] = rgb.map(f32_to_u8);
+    [r, g, b, alpha]
+}
@@ -0,0 +1,134 @@
+use crate::{BlendMode, Limage, LimageRgb, LimageRgba, PasteOptions};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Layer {
+    pub name: String,
+    // draw into it with the usual `Limage` methods
+    pub image: LimageRgba,
+    pub offset: (i32, i32),
+    pub opacity: f32,
+    pub visible: bool,
+    pub mode: BlendMode,
+}
+
+impl Layer {
+    pub fn new(name: &str, image: LimageRgba) -> Self {
+        Layer {
+            name: name.to_string(),
+            image,
+            offset: (0, 0),
+            opacity: 1.0,
+            visible: true,
+            mode: BlendMode::Normal,
+        }
+    }
+    pub fn with_offset(mut self, offset: (i32, i32)) -> Self {
+        self.offset = offset;
+        self
+    }
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity;
+        self
+    }
+    pub fn with_visible(mut self, visible: bool) -> Self {
+        self.visible = visible;
+        self
+    }
+    pub fn with_mode(mut self, mode: BlendMode) -> Self {
+        self.mode = mode;
+        self
+    }
+}
+
+// layers are kept bottom to top. names don't have to be unique, lookups by name find the
+// lowest layer with that name
+#[derive(Clone, Debug, PartialEq)]
+pub struct LayerStack {
+    width: u32,
+    height: u32,
+    pub background: [u8; 4],
+    layers: Vec<Layer>,
+}
+
+impl LayerStack {
+    pub fn new(width: u32, height: u32) -> Self {
+        LayerStack { width, height, background: [0; 4], layers: vec![] }
+    }
+    pub fn with_background(mut self, background: [u8; 4]) -> Self {
+        self.background = background;
+        self
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    // puts `layer` on top
+    pub fn push_layer(&mut self, layer: Layer) -> &mut Layer {
+        self.layers.push(layer);
+        self.layers.last_mut().unwrap()
+    }
+    // a transparent layer the size of the stack, on top
+    pub fn new_layer(&mut self, name: &str) -> &mut Layer {
+        self.push_layer(Layer::new(name, LimageRgba::new(self.width, self.height)))
+    }
+    pub fn insert_layer(&mut self, index: usize, layer: Layer) {
+        self.layers.insert(index.min(self.layers.len()), layer);
+    }
+    pub fn remove_layer(&mut self, name: &str) -> Option<Layer> {
+        let i = self.index_of(name)?;
+        Some(self.layers.remove(i))
+    }
+    // moves the layer to `index` counted from the bottom, returns false if there is no such layer
+    pub fn move_layer(&mut self, name: &str, index: usize) -> bool {
+        match self.index_of(name) {
+            Some(i) => {
+                let layer = self.layers.remove(i);
+                self.insert_layer(index, layer);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.layers.iter().position(|l| l.name == name)
+    }
+    pub fn layer(&self, name: &str) -> Option<&Layer> {
+        self.layers.iter().find(|l| l.name == name)
+    }
+    pub fn layer_mut(&mut self, name: &str) -> Option<&mut Layer> {
+        self.layers.iter_mut().find(|l| l.name == name)
+    }
+    pub fn layers(&self) -> &[Layer] {
+        &self.layers
+    }
+    pub fn layers_mut(&mut self) -> &mut [Layer] {
+        &mut self.layers
+    }
+
+    fn composite<L: Limage>(&self, out: &mut L) {
+        for layer in self.layers.iter().filter(|l| l.visible) {
+            let options = PasteOptions::new().with_opacity(layer.opacity).with_mode(layer.mode);
+            out.paste_with(layer.offset, &layer.image, &options);
+        }
+    }
+
+    pub fn flatten_rgba(&self) -> LimageRgba {
+        let mut out = LimageRgba::new(self.width, self.height);
+        out.imgbuff.pixels_mut().for_each(|p| p.0 = self.background);
+        self.composite(&mut out);
+        out
+    }
+
+    // the background is treated as opaque
+    pub fn flatten_rgb(&self) -> LimageRgb {
+        let [r, g, b, _] = self.background;
+        let mut out = LimageRgb::new(self.width, self.height).with_color([r, g, b]);
+        self.composite(&mut out);
+        out
+    }
+}
@@ -1,19 +1,51 @@
-use image::{ImageBuffer, ImageResult, Pixel, RgbImage, Rgb, Rgba, RgbaImage, imageops::{FilterType, self}, DynamicImage};
+use image::{ImageBuffer, Pixel, GrayImage, Luma, RgbImage, Rgb, Rgb32FImage, Rgba, RgbaImage, imageops, DynamicImage};
 
-use imageproc::drawing::{draw_text_mut, text_size};
-use rusttype::{Font, Scale};
 use reqwest;
 use rand;
+use shiterators::{FillRule, StrokeStyle};
+
+pub use image::imageops::FilterType;
+
+mod blend;
+mod canvas;
+mod color;
+pub mod css;
+mod error;
+mod fill;
+mod filter;
+mod font;
+mod gradient;
+mod layer;
+mod paste;
+mod quantize;
+mod resize;
+mod text;
+mod transform;
+pub use blend::BlendMode;
+pub use canvas::Canvas;
+pub use color::Color;
+pub use error::LimageError;
+pub use fill::Connectivity;
+pub use filter::{EdgeMode, Kernel};
+pub use font::{FontHandle, FontRegistry, GlyphBox, LineMetrics, TextMetrics, TextScale};
+pub use gradient::{Gradient, GradientShape, Interpolation, Spread};
+pub use layer::{Layer, LayerStack};
+pub use paste::{Anchor, PasteOptions};
+pub use quantize::{Dither, Palette, Quantizer};
+pub use resize::ResizeMode;
+pub use text::{TextAlign, TextBlock, TextLayout, TextRun, VerticalAlign};
+pub use transform::{Affine, Sampling};
+
 pub trait Limage {
-    type ImgType;
+    type ImgType: Limage;
 
     fn new(width: u32, height: u32) -> Self::ImgType;
 
-    fn open(path: &str) -> Result<Self::ImgType, String>;
+    fn open(path: &str) -> Result<Self::ImgType, LimageError>;
 
     fn with_color(self, color: [u8; 3]) -> Self::ImgType;
 
-    fn save(&self, path: &str) -> ImageResult<()>;
+    fn save(&self, path: &str) -> Result<(), LimageError>;
 
     fn width(&self) -> u32;
 
@@ -30,10 +62,191 @@ pub trait Limage {
 
     fn get_rgb(&self, p: (i32, i32)) -> Option<[u8; 3]>;
 
+    fn get_rgba(&self, p: (i32, i32)) -> Option<[u8; 4]> {
+        self.get_rgb(p).map(|[r, g, b]| [r, g, b, 255])
+    }
+
+    // channels in 0..1 at the image's own precision, float images may go above 1
+    fn get_rgba_f32(&self, p: (i32, i32)) -> Option<[f32; 4]> {
+        self.get_rgba(p).map(|c| c.map(u8_to_f32))
+    }
+
+    // clamped to 0..1 on integer images, images without an alpha channel drop it
+    fn put_rgba_f32(&mut self, p: (i32, i32), color: [f32; 4]) {
+        let [r, g, b, _] = color;
+        self.put_rgb(p, [r, g, b].map(f32_to_u8));
+    }
+
+    // composites `color` over the pixel instead of overwriting it
+    fn blend_rgba(&mut self, p: (i32, i32), color: [u8; 4]) {
+        self.blend_rgba_with(p, color, BlendMode::Normal);
+    }
+
+    fn blend_rgba_with(&mut self, p: (i32, i32), color: [u8; 4], mode: BlendMode) {
+        if color[3] == 0 {
+            return;
+        }
+        if let Some([r, g, b]) = self.get_rgb(p) {
+            let [r, g, b, _] = blend::composite_u8([r, g, b, 255], color, mode);
+            self.put_rgb(p, [r, g, b]);
+        }
+    }
+
     fn paste(&mut self, position: (i32, i32), other: &Self);
 
+    // paint bucket, `tolerance` is the euclidean rgb distance from the seed color still counted
+    // as the same region. returns the bounding box of the filled pixels, or None if nothing got
+    // filled because the seed is outside the image or the tolerance rules out even the seed
+    fn flood_fill(&mut self, seed: (i32, i32), color: [u8; 3], tolerance: f32, connectivity: Connectivity) -> Option<((i32, i32), (i32, i32))> {
+        fill::flood_fill(self, seed, color, tolerance, connectivity)
+    }
+
+    // like `paste` but with a blend mode and an opacity in 0..1 applied on top of `other`'s alpha
+    fn paste_blend<L: Limage + ?Sized>(&mut self, position: (i32, i32), other: &L, mode: BlendMode, opacity: f32) {
+        self.paste_with(position, other, &PasteOptions::new().with_mode(mode).with_opacity(opacity));
+    }
+
+    // composites any image type over this one, `position` is relative to `options.anchor`
+    fn paste_with<L: Limage + ?Sized>(&mut self, position: (i32, i32), other: &L, options: &PasteOptions) {
+        let opacity = options.opacity.clamp(0.0, 1.0);
+        let (w, h) = (other.width() as i32, other.height() as i32);
+        let (x0, y0) = options.anchor.place((self.width(), self.height()), (other.width(), other.height()), position);
+        // only walk the part that lands inside this image
+        let (x_start, x_end) = ((-x0).max(0), w.min(self.width() as i32 - x0));
+        let (y_start, y_end) = ((-y0).max(0), h.min(self.height() as i32 - y0));
+        for y in y_start..y_end {
+            for x in x_start..x_end {
+                let mask = match options.mask {
+                    Some(mask) => mask.get_luma((x, y)).unwrap_or(0) as f32 / 255.0,
+                    None => 1.0,
+                };
+                // blended at full precision, so 16-bit and float images keep theirs
+                let p = (x0 + x, y0 + y);
+                if let (Some([r, g, b, a]), Some(dst)) = (other.get_rgba_f32((x, y)), self.get_rgba_f32(p)) {
+                    let a = a * opacity * mask;
+                    if a > 0.0 {
+                        self.put_rgba_f32(p, blend::composite(dst, [r, g, b, a], options.mode));
+                    }
+                }
+            }
+        }
+    }
+
+    // maps every pixel to its nearest palette color
+    fn remap(&mut self, palette: &Palette, dither: Dither) {
+        let w = self.width() as i32;
+        for (i, c) in quantize::remap(self, palette, dither).into_iter().enumerate() {
+            let i = i as i32;
+            self.put_rgb((i % w, i / w), c);
+        }
+    }
+
+    // reduces the image to at most `size` colors and returns the palette it used
+    fn quantize(&mut self, size: usize, quantizer: Quantizer, dither: Dither) -> Palette {
+        let palette = Palette::from_image(self, size, quantizer);
+        self.remap(&palette, dither);
+        palette
+    }
+
+    // color is filtered premultiplied by alpha so transparent pixels don't bleed into their neighbours
+    fn convolve(&mut self, kernel: &Kernel, edge: EdgeMode) {
+        let pixels = filter::convolve(self, kernel, edge);
+        put_pixels(self, pixels);
+    }
+    fn box_blur(&mut self, radius: u32) {
+        self.convolve(&Kernel::box_blur(radius), EdgeMode::Clamp);
+    }
+    fn gaussian_blur(&mut self, sigma: f32) {
+        self.convolve(&Kernel::gaussian(sigma), EdgeMode::Clamp);
+    }
+    fn sharpen(&mut self) {
+        self.convolve(&Kernel::sharpen(), EdgeMode::Clamp);
+    }
+    // `threshold` is the smallest difference from the blurred image that still gets sharpened
+    fn unsharp_mask(&mut self, sigma: f32, amount: f32, threshold: u8) {
+        let pixels = filter::unsharp_mask(self, sigma, amount, threshold);
+        put_pixels(self, pixels);
+    }
+    fn emboss(&mut self) {
+        self.convolve(&Kernel::emboss(), EdgeMode::Clamp);
+    }
+    fn sobel(&mut self) {
+        let pixels = filter::sobel(self);
+        put_pixels(self, pixels);
+    }
+    fn laplacian(&mut self) {
+        self.convolve(&Kernel::laplacian(), EdgeMode::Clamp);
+    }
+
     fn as_resized(self, width: u32, height: u32) -> Self;
 
+    // `FilterType::Nearest` keeps pixel art crisp
+    fn resize_with(&self, width: u32, height: u32, filter: FilterType, mode: ResizeMode) -> Self::ImgType;
+
+    // inclusive corners in any order, clipped to the image
+    fn crop(&self, p1: (i32, i32), p2: (i32, i32)) -> Self::ImgType {
+        let (x0, y0, w, h) = transform::crop_rect(self.width(), self.height(), p1, p2);
+        let (x0, y0) = (x0 as i32, y0 as i32);
+        transform::rearrange(self, w, h, |x, y| (x0 + x, y0 + y))
+    }
+
+    fn flip_horizontal(&self) -> Self::ImgType {
+        let w = self.width() as i32;
+        transform::rearrange(self, self.width(), self.height(), |x, y| (w - 1 - x, y))
+    }
+
+    fn flip_vertical(&self) -> Self::ImgType {
+        let h = self.height() as i32;
+        transform::rearrange(self, self.width(), self.height(), |x, y| (x, h - 1 - y))
+    }
+
+    // clockwise
+    fn rotate90(&self) -> Self::ImgType {
+        let h = self.height() as i32;
+        transform::rearrange(self, self.height(), self.width(), |x, y| (y, h - 1 - x))
+    }
+
+    fn rotate180(&self) -> Self::ImgType {
+        let (w, h) = (self.width() as i32, self.height() as i32);
+        transform::rearrange(self, self.width(), self.height(), |x, y| (w - 1 - x, h - 1 - y))
+    }
+
+    fn rotate270(&self) -> Self::ImgType {
+        let w = self.width() as i32;
+        transform::rearrange(self, self.height(), self.width(), |x, y| (w - 1 - y, x))
+    }
+
+    // `matrix` maps source positions to output positions, the output keeps the source size and
+    // whatever nothing maps to is transparent, or black on images without an alpha channel
+    fn affine_transform(&self, matrix: Affine, sampling: Sampling) -> Self::ImgType {
+        let (w, h) = (self.width(), self.height());
+        let mut out = Self::new(w, h);
+        put_pixels(&mut out, transform::affine(self, w, h, matrix, sampling));
+        out
+    }
+
+    // rotates clockwise around the center. with `expand` the output grows to fit the rotated
+    // corners, otherwise it keeps the source size and the corners get cut off. uncovered areas
+    // are filled like in `affine_transform`
+    fn rotate(&self, angle: f32, sampling: Sampling, expand: bool) -> Self::ImgType {
+        let (w, h) = (self.width() as f32, self.height() as f32);
+        let (sin, cos) = angle.sin_cos();
+        let (out_w, out_h) = if expand {
+            // shaved a little so float noise at right angles doesn't add a row
+            let out_w = (w * cos.abs() + h * sin.abs() - 1e-3).ceil().max(1.0);
+            let out_h = (w * sin.abs() + h * cos.abs() - 1e-3).ceil().max(1.0);
+            (out_w as u32, out_h as u32)
+        } else {
+            (self.width(), self.height())
+        };
+        let matrix = Affine::translate(-(w - 1.0) / 2.0, -(h - 1.0) / 2.0)
+            .then(Affine::rotate(angle))
+            .then(Affine::translate((out_w as f32 - 1.0) / 2.0, (out_h as f32 - 1.0) / 2.0));
+        let mut out = Self::new(out_w, out_h);
+        put_pixels(&mut out, transform::affine(self, out_w, out_h, matrix, sampling));
+        out
+    }
+
     fn as_rgb_buf(&self) -> Vec<u8>;
 
     fn draw_rectangle(&mut self, p1: (i32, i32), p2: (i32, i32), color: [u8; 3]) {
@@ -56,6 +269,153 @@ pub trait Limage {
             self.put_rgb(p, color);
         }
     }
+
+    fn draw_ellipse(&mut self, origin: (i32, i32), radii: (u32, u32), color: [u8; 3]) {
+        for p in shiterators::Ellipse::new(origin, radii) {
+            self.put_rgb(p, color);
+        }
+    }
+    fn draw_filled_ellipse(&mut self, origin: (i32, i32), radii: (u32, u32), color: [u8; 3]) {
+        for p in shiterators::FilledEllipse::new(origin, radii) {
+            self.put_rgb(p, color);
+        }
+    }
+    // angles in radians, clockwise on screen starting at 3 o'clock
+    fn draw_arc(&mut self, origin: (i32, i32), radius: u32, start: f32, end: f32, color: [u8; 3]) {
+        for p in shiterators::Arc::new(origin, radius, start, end) {
+            self.put_rgb(p, color);
+        }
+    }
+    fn draw_pie(&mut self, origin: (i32, i32), radius: u32, start: f32, end: f32, color: [u8; 3]) {
+        for p in shiterators::Pie::new(origin, radius, start, end) {
+            self.put_rgb(p, color);
+        }
+    }
+    // `points` are the start point, any number of control points and the end point
+    fn draw_bezier(&mut self, points: &[(i32, i32)], color: [u8; 3]) -> Result<(), LimageError> {
+        for p in shiterators::Bezier::new(points)? {
+            self.put_rgb(p, color);
+        }
+        Ok(())
+    }
+    fn draw_line_styled(&mut self, p1: (i32, i32), p2: (i32, i32), style: StrokeStyle, color: [u8; 3]) -> Result<(), LimageError> {
+        for p in shiterators::Stroke::line(p1, p2, style)? {
+            self.put_rgb(p, color);
+        }
+        Ok(())
+    }
+    fn draw_path(&mut self, points: &[(i32, i32)], style: StrokeStyle, color: [u8; 3]) -> Result<(), LimageError> {
+        for p in shiterators::Stroke::path(points, style)? {
+            self.put_rgb(p, color);
+        }
+        Ok(())
+    }
+    fn draw_circuit(&mut self, points: &[(i32, i32)], style: StrokeStyle, color: [u8; 3]) -> Result<(), LimageError> {
+        for p in shiterators::Stroke::circuit(points, style)? {
+            self.put_rgb(p, color);
+        }
+        Ok(())
+    }
+
+    fn draw_polygon(&mut self, points: &[(i32, i32)], color: [u8; 3]) -> Result<(), LimageError> {
+        for p in shiterators::PathCircuit::new_circuit(&points.to_vec())? {
+            self.put_rgb(p, color);
+        }
+        Ok(())
+    }
+    fn fill_polygon(&mut self, points: &[(i32, i32)], rule: FillRule, color: [u8; 3]) -> Result<(), LimageError> {
+        for p in shiterators::Polygon::new(points, rule)? {
+            self.put_rgb(p, color);
+        }
+        Ok(())
+    }
+
+    fn draw_shape<I: IntoIterator<Item = (i32, i32)>>(&mut self, shape: I, color: [u8; 4], mode: BlendMode) {
+        for p in shape {
+            self.blend_rgba_with(p, color, mode);
+        }
+    }
+
+    // blends the gradient's color at each pixel of the shape
+    fn draw_shape_gradient<I: IntoIterator<Item = (i32, i32)>>(&mut self, shape: I, gradient: &Gradient, mode: BlendMode) {
+        for p in shape {
+            self.blend_rgba_with(p, gradient.color_at(p), mode);
+        }
+    }
+
+    fn fill_gradient(&mut self, gradient: &Gradient) {
+        let (w, h) = (self.width() as i32, self.height() as i32);
+        if w > 0 && h > 0 {
+            self.draw_shape_gradient(shiterators::Rectangle::new((0, 0), (w - 1, h - 1)), gradient, BlendMode::Normal);
+        }
+    }
+    fn fill_rectangle_gradient(&mut self, p1: (i32, i32), p2: (i32, i32), gradient: &Gradient) {
+        self.draw_shape_gradient(shiterators::Rectangle::new(p1, p2), gradient, BlendMode::Normal);
+    }
+    fn fill_disc_gradient(&mut self, origin: (i32, i32), radius: u32, gradient: &Gradient) {
+        self.draw_shape_gradient(shiterators::Disc::new(origin, radius), gradient, BlendMode::Normal);
+    }
+    fn fill_polygon_gradient(&mut self, points: &[(i32, i32)], rule: FillRule, gradient: &Gradient) -> Result<(), LimageError> {
+        self.draw_shape_gradient(shiterators::Polygon::new(points, rule)?, gradient, BlendMode::Normal);
+        Ok(())
+    }
+
+    // blends `color` scaled by each pixel's coverage, for the anti-aliased shiterators
+    fn draw_shape_aa<I: IntoIterator<Item = ((i32, i32), f32)>>(&mut self, shape: I, color: [u8; 4], mode: BlendMode) {
+        for (p, coverage) in shape {
+            let a = (color[3] as f32 * coverage.clamp(0.0, 1.0)).round() as u8;
+            self.blend_rgba_with(p, [color[0], color[1], color[2], a], mode);
+        }
+    }
+
+    fn draw_line_aa(&mut self, p1: (f32, f32), p2: (f32, f32), color: [u8; 3]) {
+        let [r, g, b] = color;
+        self.draw_shape_aa(shiterators::LineAA::new(p1, p2), [r, g, b, 255], BlendMode::Normal);
+    }
+    fn draw_circle_aa(&mut self, origin: (f32, f32), radius: f32, color: [u8; 3]) {
+        let [r, g, b] = color;
+        self.draw_shape_aa(shiterators::CircleAA::new(origin, radius), [r, g, b, 255], BlendMode::Normal);
+    }
+    fn draw_disc_aa(&mut self, origin: (f32, f32), radius: f32, color: [u8; 3]) {
+        let [r, g, b] = color;
+        self.draw_shape_aa(shiterators::DiscAA::new(origin, radius), [r, g, b, 255], BlendMode::Normal);
+    }
+
+    fn draw_rectangle_rgba(&mut self, p1: (i32, i32), p2: (i32, i32), color: [u8; 4]) {
+        for p in shiterators::Rectangle::new(p1, p2) {
+            self.blend_rgba(p, color);
+        }
+    }
+    fn draw_circle_rgba(&mut self, origin: (i32, i32), radius: u32, color: [u8; 4]) {
+        for p in shiterators::Circle::new(origin, radius) {
+            self.blend_rgba(p, color);
+        }
+    }
+    fn draw_disc_rgba(&mut self, origin: (i32, i32), radius: u32, color: [u8; 4]) {
+        for p in shiterators::Disc::new(origin, radius) {
+            self.blend_rgba(p, color);
+        }
+    }
+    fn draw_line_rgba(&mut self, p1: (i32, i32), p2: (i32, i32), color: [u8; 4]) {
+        for p in shiterators::Line::new(p1, p2) {
+            self.blend_rgba(p, color);
+        }
+    }
+
+    // `pos` is the top left of the line, glyph edges are blended by how much of the pixel they cover
+    fn write_text(&mut self, pos: (i32, i32), color: [u8; 4], text: &str, scale: impl Into<TextScale>, font: &FontHandle) {
+        text::draw_text(self, pos, color, text, scale.into(), font);
+    }
+    // `origin` is the left end of the baseline, so text lines up with other shapes to a fraction of a pixel
+    fn write_text_baseline(&mut self, origin: (f32, f32), color: [u8; 4], text: &str, scale: impl Into<TextScale>, font: &FontHandle) {
+        text::draw_text_baseline(self, origin, color, text, scale.into(), font);
+    }
+    // draws what `TextLayout::layout` worked out, with the same font
+    fn write_text_block(&mut self, block: &TextBlock, color: [u8; 4], font: &FontHandle) {
+        for run in &block.runs {
+            self.write_text(run.position, color, &run.text, block.scale, font);
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -70,11 +430,8 @@ impl Limage for LimageRgb {
         Self { imgbuff: ImageBuffer::new(width, height) }
     }
 
-    fn open(path: &str) -> Result<Self::ImgType, String> {
-        match image::open(path) {
-            Ok(img) => Ok(Self { imgbuff: img.into_rgb8() }),
-            Err(e) => Err(e.to_string())
-        }
+    fn open(path: &str) -> Result<Self::ImgType, LimageError> {
+        Ok(Self { imgbuff: image::open(path)?.into_rgb8() })
     }
 
     fn with_color(mut self, color: [u8; 3]) -> Self::ImgType {
@@ -84,8 +441,8 @@ impl Limage for LimageRgb {
         self
     }
     #[inline]
-    fn save(&self, path: &str) -> ImageResult<()> {
-        self.imgbuff.save(path)
+    fn save(&self, path: &str) -> Result<(), LimageError> {
+        Ok(self.imgbuff.save(path)?)
     }
     #[inline]
     fn width(&self) -> u32 {
@@ -126,6 +483,10 @@ impl Limage for LimageRgb {
         self.imgbuff = imageops::resize(&self.imgbuff, width, height, FilterType::Lanczos3);
         self
     }
+
+    fn resize_with(&self, width: u32, height: u32, filter: FilterType, mode: ResizeMode) -> Self::ImgType {
+        Self { imgbuff: resize::resize_with(&self.imgbuff, width, height, filter, mode, |[r, g, b, _]| Rgb([r, g, b])) }
+    }
 }
 
 
@@ -142,13 +503,6 @@ impl LimageRgba {
                 .put_pixel(x as u32, y as u32, image::Rgba(color));
         }
     }
-    pub fn get_rgba(&self, p: (i32, i32)) -> Option<[u8; 4]> {
-        if self.in_bounds(p) {
-            let (x, y) = p;
-            return Some(self.imgbuff.get_pixel(x as u32, y as u32).0);
-        }
-        None
-    }
 }
 
 impl Limage for LimageRgba {
@@ -158,11 +512,8 @@ impl Limage for LimageRgba {
         Self { imgbuff: ImageBuffer::new(width, height) }
     }
 
-    fn open(path: &str) -> Result<Self::ImgType, String> {
-        match image::open(path) {
-            Ok(img) => Ok(Self { imgbuff: img.into_rgba8() }),
-            Err(e) => Err(e.to_string())
-        }
+    fn open(path: &str) -> Result<Self::ImgType, LimageError> {
+        Ok(Self { imgbuff: image::open(path)?.into_rgba8() })
     }
 
     fn with_color(mut self, color: [u8; 3]) -> Self::ImgType {
@@ -175,8 +526,8 @@ impl Limage for LimageRgba {
         self
     }
 
-    fn save(&self, path: &str) -> ImageResult<()> {
-        self.imgbuff.save(path)
+    fn save(&self, path: &str) -> Result<(), LimageError> {
+        Ok(self.imgbuff.save(path)?)
     }
 
     fn width(&self) -> u32 {
@@ -204,6 +555,35 @@ impl Limage for LimageRgba {
         None
     }
 
+    fn get_rgba(&self, p: (i32, i32)) -> Option<[u8; 4]> {
+        if self.in_bounds(p) {
+            let (x, y) = p;
+            return Some(self.imgbuff.get_pixel(x as u32, y as u32).0);
+        }
+        None
+    }
+
+    fn put_rgba_f32(&mut self, p: (i32, i32), color: [f32; 4]) {
+        self.put_rgba(p, color.map(f32_to_u8));
+    }
+
+    fn blend_rgba_with(&mut self, p: (i32, i32), color: [u8; 4], mode: BlendMode) {
+        if let Some(dst) = self.get_rgba(p) {
+            self.put_rgba(p, blend::composite_u8(dst, color, mode));
+        }
+    }
+
+    // keeps each pixel's alpha
+    fn remap(&mut self, palette: &Palette, dither: Dither) {
+        let w = self.width() as i32;
+        for (i, [r, g, b]) in quantize::remap(self, palette, dither).into_iter().enumerate() {
+            let p = (i as i32 % w, i as i32 / w);
+            if let Some([.., a]) = self.get_rgba(p) {
+                self.put_rgba(p, [r, g, b, a]);
+            }
+        }
+    }
+
     fn paste(&mut self, position: (i32, i32), other: &Self) {
         imageops::overlay(&mut self.imgbuff, &other.imgbuff, position.0 as i64, position.1 as i64);
     }
@@ -223,28 +603,426 @@ impl Limage for LimageRgba {
         self.imgbuff = imageops::resize(&self.imgbuff, width, height, FilterType::Lanczos3);
         self
     }
+
+    fn resize_with(&self, width: u32, height: u32, filter: FilterType, mode: ResizeMode) -> Self::ImgType {
+        Self { imgbuff: resize::resize_with(&self.imgbuff, width, height, filter, mode, Rgba) }
+    }
 }
 
-impl LimageRgba {
-    pub fn write_text(&mut self, pos: (i32, i32), color: [u8; 4], text: &str, size: f32, font: &str) {
-        let font = std::fs::read(format!("./assets/{font}")).unwrap();
-        let font = Font::try_from_vec(font).unwrap();
-        let scale = Scale {
-            x: size * 2.0,
-            y: size,
-        };
-        draw_text_mut(&mut self.imgbuff, Rgba(color), pos.0, pos.1, scale, &font, text);
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LimageLuma {
+    pub imgbuff: GrayImage,
+}
+
+impl LimageLuma {
+    pub fn put_luma(&mut self, p: (i32, i32), value: u8) {
+        if self.in_bounds(p) {
+            let (x, y) = p;
+            self.imgbuff.put_pixel(x as u32, y as u32, Luma([value]));
+        }
+    }
+    pub fn get_luma(&self, p: (i32, i32)) -> Option<u8> {
+        if self.in_bounds(p) {
+            let (x, y) = p;
+            return Some(self.imgbuff.get_pixel(x as u32, y as u32).0[0]);
+        }
+        None
     }
 }
 
-pub fn sizeof_text(text: &str, size: f32, font: &str) -> (i32, i32) {
-    let font = std::fs::read(format!("./assets/{font}")).unwrap();
-    let font = Font::try_from_vec(font).unwrap();
-    let scale = Scale {
-        x: size * 2.0,
-        y: size,
-    };
-    text_size(scale, &font, text)
+impl Limage for LimageLuma {
+    type ImgType = LimageLuma;
+
+    fn new(width: u32, height: u32) -> Self::ImgType {
+        Self { imgbuff: ImageBuffer::new(width, height) }
+    }
+
+    fn open(path: &str) -> Result<Self::ImgType, LimageError> {
+        Ok(Self { imgbuff: image::open(path)?.into_luma8() })
+    }
+
+    fn with_color(mut self, color: [u8; 3]) -> Self::ImgType {
+        let luma = image::Rgb(color).to_luma();
+        self.imgbuff.pixels_mut().for_each(|p| *p = luma);
+        self
+    }
+
+    fn save(&self, path: &str) -> Result<(), LimageError> {
+        Ok(self.imgbuff.save(path)?)
+    }
+
+    fn width(&self) -> u32 {
+        self.imgbuff.width()
+    }
+
+    fn height(&self) -> u32 {
+        self.imgbuff.height()
+    }
+
+    fn put_rgb(&mut self, p: (i32, i32), color: [u8; 3]) {
+        if self.in_bounds(p) {
+            let (x, y) = p;
+            self.imgbuff
+                .put_pixel(x as u32, y as u32, image::Rgb(color).to_luma());
+        }
+    }
+
+    fn get_rgb(&self, p: (i32, i32)) -> Option<[u8; 3]> {
+        self.get_luma(p).map(|l| [l; 3])
+    }
+
+    fn paste(&mut self, position: (i32, i32), other: &Self) {
+        imageops::overlay(&mut self.imgbuff, &other.imgbuff, position.0 as i64, position.1 as i64);
+    }
+
+    fn as_rgb_buf(&self) -> Vec<u8> {
+        self.imgbuff.as_raw().iter().flat_map(|&l| [l; 3]).collect()
+    }
+
+    fn as_resized(mut self, width: u32, height: u32) -> Self {
+        self.imgbuff = imageops::resize(&self.imgbuff, width, height, FilterType::Lanczos3);
+        self
+    }
+
+    fn resize_with(&self, width: u32, height: u32, filter: FilterType, mode: ResizeMode) -> Self::ImgType {
+        Self { imgbuff: resize::resize_with(&self.imgbuff, width, height, filter, mode, |[r, g, b, _]| Rgb([r, g, b]).to_luma()) }
+    }
+}
+
+// writes a row-major buffer covering the whole image
+fn put_pixels<L: Limage + ?Sized>(img: &mut L, pixels: Vec<[f32; 4]>) {
+    let w = img.width() as i32;
+    for (i, color) in pixels.into_iter().enumerate() {
+        let i = i as i32;
+        img.put_rgba_f32((i % w, i / w), color);
+    }
+}
+
+#[inline]
+fn u8_to_u16(c: u8) -> u16 {
+    c as u16 * 257
+}
+
+#[inline]
+fn u16_to_u8(c: u16) -> u8 {
+    ((c as u32 + 128) / 257) as u8
+}
+
+#[inline]
+fn u16_to_f32(c: u16) -> f32 {
+    c as f32 / 65535.0
+}
+
+#[inline]
+fn f32_to_u16(c: f32) -> u16 {
+    (c.clamp(0.0, 1.0) * 65535.0).round() as u16
+}
+
+#[inline]
+fn u8_to_f32(c: u8) -> f32 {
+    c as f32 / 255.0
+}
+
+#[inline]
+fn f32_to_u8(c: f32) -> u8 {
+    (c.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LimageRgb16 {
+    pub imgbuff: ImageBuffer<Rgb<u16>, Vec<u16>>,
+}
+
+impl LimageRgb16 {
+    pub fn put_rgb16(&mut self, p: (i32, i32), color: [u16; 3]) {
+        if self.in_bounds(p) {
+            let (x, y) = p;
+            self.imgbuff.put_pixel(x as u32, y as u32, Rgb(color));
+        }
+    }
+    pub fn get_rgb16(&self, p: (i32, i32)) -> Option<[u16; 3]> {
+        if self.in_bounds(p) {
+            let (x, y) = p;
+            return Some(self.imgbuff.get_pixel(x as u32, y as u32).0);
+        }
+        None
+    }
+}
+
+impl Limage for LimageRgb16 {
+    type ImgType = LimageRgb16;
+
+    fn new(width: u32, height: u32) -> Self::ImgType {
+        Self { imgbuff: ImageBuffer::new(width, height) }
+    }
+
+    fn open(path: &str) -> Result<Self::ImgType, LimageError> {
+        Ok(Self { imgbuff: image::open(path)?.into_rgb16() })
+    }
+
+    fn with_color(mut self, color: [u8; 3]) -> Self::ImgType {
+        let color = Rgb(color.map(u8_to_u16));
+        self.imgbuff.pixels_mut().for_each(|p| *p = color);
+        self
+    }
+
+    fn save(&self, path: &str) -> Result<(), LimageError> {
+        Ok(self.imgbuff.save(path)?)
+    }
+
+    fn width(&self) -> u32 {
+        self.imgbuff.width()
+    }
+
+    fn height(&self) -> u32 {
+        self.imgbuff.height()
+    }
+
+    fn put_rgb(&mut self, p: (i32, i32), color: [u8; 3]) {
+        self.put_rgb16(p, color.map(u8_to_u16));
+    }
+
+    fn get_rgb(&self, p: (i32, i32)) -> Option<[u8; 3]> {
+        self.get_rgb16(p).map(|c| c.map(u16_to_u8))
+    }
+
+    fn get_rgba_f32(&self, p: (i32, i32)) -> Option<[f32; 4]> {
+        self.get_rgb16(p).map(|[r, g, b]| [u16_to_f32(r), u16_to_f32(g), u16_to_f32(b), 1.0])
+    }
+
+    fn put_rgba_f32(&mut self, p: (i32, i32), color: [f32; 4]) {
+        let [r, g, b, _] = color;
+        self.put_rgb16(p, [r, g, b].map(f32_to_u16));
+    }
+
+    fn blend_rgba_with(&mut self, p: (i32, i32), color: [u8; 4], mode: BlendMode) {
+        if let Some([r, g, b]) = self.get_rgb16(p) {
+            let [r, g, b, _] = blend::composite([r, g, b, u16::MAX].map(u16_to_f32), color.map(u8_to_f32), mode);
+            self.put_rgb16(p, [r, g, b].map(f32_to_u16));
+        }
+    }
+
+    fn paste(&mut self, position: (i32, i32), other: &Self) {
+        imageops::overlay(&mut self.imgbuff, &other.imgbuff, position.0 as i64, position.1 as i64);
+    }
+
+    fn as_rgb_buf(&self) -> Vec<u8> {
+        self.imgbuff.as_raw().iter().map(|&c| u16_to_u8(c)).collect()
+    }
+
+    fn as_resized(mut self, width: u32, height: u32) -> Self {
+        self.imgbuff = imageops::resize(&self.imgbuff, width, height, FilterType::Lanczos3);
+        self
+    }
+
+    fn resize_with(&self, width: u32, height: u32, filter: FilterType, mode: ResizeMode) -> Self::ImgType {
+        Self { imgbuff: resize::resize_with(&self.imgbuff, width, height, filter, mode, |[r, g, b, _]| Rgb([r, g, b].map(u8_to_u16))) }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LimageRgba16 {
+    pub imgbuff: ImageBuffer<Rgba<u16>, Vec<u16>>,
+}
+
+impl LimageRgba16 {
+    pub fn put_rgba16(&mut self, p: (i32, i32), color: [u16; 4]) {
+        if self.in_bounds(p) {
+            let (x, y) = p;
+            self.imgbuff.put_pixel(x as u32, y as u32, Rgba(color));
+        }
+    }
+    pub fn get_rgba16(&self, p: (i32, i32)) -> Option<[u16; 4]> {
+        if self.in_bounds(p) {
+            let (x, y) = p;
+            return Some(self.imgbuff.get_pixel(x as u32, y as u32).0);
+        }
+        None
+    }
+}
+
+impl Limage for LimageRgba16 {
+    type ImgType = LimageRgba16;
+
+    fn new(width: u32, height: u32) -> Self::ImgType {
+        Self { imgbuff: ImageBuffer::new(width, height) }
+    }
+
+    fn open(path: &str) -> Result<Self::ImgType, LimageError> {
+        Ok(Self { imgbuff: image::open(path)?.into_rgba16() })
+    }
+
+    fn with_color(mut self, color: [u8; 3]) -> Self::ImgType {
+        let color = Rgb(color.map(u8_to_u16)).to_rgba();
+        self.imgbuff.pixels_mut().for_each(|p| *p = color);
+        self
+    }
+
+    fn save(&self, path: &str) -> Result<(), LimageError> {
+        Ok(self.imgbuff.save(path)?)
+    }
+
+    fn width(&self) -> u32 {
+        self.imgbuff.width()
+    }
+
+    fn height(&self) -> u32 {
+        self.imgbuff.height()
+    }
+
+    fn put_rgb(&mut self, p: (i32, i32), color: [u8; 3]) {
+        let [r, g, b] = color.map(u8_to_u16);
+        self.put_rgba16(p, [r, g, b, u16::MAX]);
+    }
+
+    fn get_rgb(&self, p: (i32, i32)) -> Option<[u8; 3]> {
+        self.get_rgba16(p).map(|[r, g, b, _]| [r, g, b].map(u16_to_u8))
+    }
+
+    fn get_rgba(&self, p: (i32, i32)) -> Option<[u8; 4]> {
+        self.get_rgba16(p).map(|c| c.map(u16_to_u8))
+    }
+
+    fn get_rgba_f32(&self, p: (i32, i32)) -> Option<[f32; 4]> {
+        self.get_rgba16(p).map(|c| c.map(u16_to_f32))
+    }
+
+    fn put_rgba_f32(&mut self, p: (i32, i32), color: [f32; 4]) {
+        self.put_rgba16(p, color.map(f32_to_u16));
+    }
+
+    fn blend_rgba_with(&mut self, p: (i32, i32), color: [u8; 4], mode: BlendMode) {
+        if let Some(dst) = self.get_rgba16(p) {
+            let out = blend::composite(dst.map(u16_to_f32), color.map(u8_to_f32), mode);
+            self.put_rgba16(p, out.map(f32_to_u16));
+        }
+    }
+
+    fn paste(&mut self, position: (i32, i32), other: &Self) {
+        imageops::overlay(&mut self.imgbuff, &other.imgbuff, position.0 as i64, position.1 as i64);
+    }
+
+    fn as_rgb_buf(&self) -> Vec<u8> {
+        self.imgbuff
+            .pixels()
+            .flat_map(|p| [p[0], p[1], p[2]].map(u16_to_u8))
+            .collect()
+    }
+
+    fn as_resized(mut self, width: u32, height: u32) -> Self {
+        self.imgbuff = imageops::resize(&self.imgbuff, width, height, FilterType::Lanczos3);
+        self
+    }
+
+    fn resize_with(&self, width: u32, height: u32, filter: FilterType, mode: ResizeMode) -> Self::ImgType {
+        Self { imgbuff: resize::resize_with(&self.imgbuff, width, height, filter, mode, |c| Rgba(c.map(u8_to_u16))) }
+    }
+}
+
+// channels are nominally 0..1 but may go above 1 for hdr content,
+// the 8-bit accessors clamp
+#[derive(Clone, Debug, PartialEq)]
+pub struct LimageRgb32F {
+    pub imgbuff: Rgb32FImage,
+}
+
+impl LimageRgb32F {
+    pub fn put_rgb32f(&mut self, p: (i32, i32), color: [f32; 3]) {
+        if self.in_bounds(p) {
+            let (x, y) = p;
+            self.imgbuff.put_pixel(x as u32, y as u32, Rgb(color));
+        }
+    }
+    pub fn get_rgb32f(&self, p: (i32, i32)) -> Option<[f32; 3]> {
+        if self.in_bounds(p) {
+            let (x, y) = p;
+            return Some(self.imgbuff.get_pixel(x as u32, y as u32).0);
+        }
+        None
+    }
+}
+
+impl Limage for LimageRgb32F {
+    type ImgType = LimageRgb32F;
+
+    fn new(width: u32, height: u32) -> Self::ImgType {
+        Self { imgbuff: ImageBuffer::new(width, height) }
+    }
+
+    fn open(path: &str) -> Result<Self::ImgType, LimageError> {
+        Ok(Self { imgbuff: image::open(path)?.into_rgb32f() })
+    }
+
+    fn with_color(mut self, color: [u8; 3]) -> Self::ImgType {
+        let color = Rgb(color.map(u8_to_f32));
+        self.imgbuff.pixels_mut().for_each(|p| *p = color);
+        self
+    }
+
+    fn save(&self, path: &str) -> Result<(), LimageError> {
+        Ok(self.imgbuff.save(path)?)
+    }
+
+    fn width(&self) -> u32 {
+        self.imgbuff.width()
+    }
+
+    fn height(&self) -> u32 {
+        self.imgbuff.height()
+    }
+
+    fn put_rgb(&mut self, p: (i32, i32), color: [u8; 3]) {
+        self.put_rgb32f(p, color.map(u8_to_f32));
+    }
+
+    fn get_rgb(&self, p: (i32, i32)) -> Option<[u8; 3]> {
+        self.get_rgb32f(p).map(|c| c.map(f32_to_u8))
+    }
+
+    fn get_rgba_f32(&self, p: (i32, i32)) -> Option<[f32; 4]> {
+        self.get_rgb32f(p).map(|[r, g, b]| [r, g, b, 1.0])
+    }
+
+    // not clamped, so hdr values survive
+    fn put_rgba_f32(&mut self, p: (i32, i32), color: [f32; 4]) {
+        let [r, g, b, _] = color;
+        self.put_rgb32f(p, [r, g, b]);
+    }
+
+    fn blend_rgba_with(&mut self, p: (i32, i32), color: [u8; 4], mode: BlendMode) {
+        if let Some([r, g, b]) = self.get_rgb32f(p) {
+            let [r, g, b, _] = blend::composite([r, g, b, 1.0], color.map(u8_to_f32), mode);
+            self.put_rgb32f(p, [r, g, b]);
+        }
+    }
+
+    fn paste(&mut self, position: (i32, i32), other: &Self) {
+        imageops::overlay(&mut self.imgbuff, &other.imgbuff, position.0 as i64, position.1 as i64);
+    }
+
+    fn as_rgb_buf(&self) -> Vec<u8> {
+        self.imgbuff.as_raw().iter().map(|&c| f32_to_u8(c)).collect()
+    }
+
+    fn as_resized(mut self, width: u32, height: u32) -> Self {
+        self.imgbuff = imageops::resize(&self.imgbuff, width, height, FilterType::Lanczos3);
+        self
+    }
+
+    fn resize_with(&self, width: u32, height: u32, filter: FilterType, mode: ResizeMode) -> Self::ImgType {
+        Self { imgbuff: resize::resize_with(&self.imgbuff, width, height, filter, mode, |[r, g, b, _]| Rgb([r, g, b].map(u8_to_f32))) }
+    }
+}
+
+// how far the glyphs `write_text` draws reach right of and below its position. only ink counts,
+// `FontHandle::measure` has the advance width and the line box
+pub fn sizeof_text(text: &str, scale: impl Into<TextScale>, font: &FontHandle) -> (i32, i32) {
+    let scale = scale.into();
+    let ascent = font.line_metrics(scale).ascent;
+    font.glyphs(text, scale, (0.0, ascent))
+        .iter()
+        .filter_map(|g| g.pixel_bounding_box())
+        .fold((0, 0), |(w, h), bb| (w.max(bb.max.x), h.max(bb.max.y)))
 }
 
 pub enum PexelMode {
@@ -253,26 +1031,36 @@ pub enum PexelMode {
     Portrait,
 }
 
-pub fn from_google(query: &str, mode: PexelMode) -> DynamicImage {
+pub fn from_google(query: &str, mode: PexelMode) -> Result<DynamicImage, LimageError> {
     let pexels_api_client = pexels::Pexels::new("kWALdmnm5cdOAOU08nEEhrFd8tdjno4QdA5bda7LuXvH2JL04AV4ebnT".to_owned());
     let shit = pexels_api_client.photo_search(query.to_string(), 256, 1);
-    let n = &shit["photos"].as_array().unwrap().len();
-    if *n == 0 { panic!("found no image with query '{}'", query) }
+    let n = match shit["photos"].as_array() {
+        Some(photos) => photos.len(),
+        None => return Err(LimageError::Network(format!("unexpected response to query '{query}'"))),
+    };
+    if n == 0 {
+        return Err(LimageError::Network(format!("found no image with query '{query}'")));
+    }
     let i: usize = rand::random::<usize>() % n;
     let mode = match mode {
         PexelMode::Original => "original",
         PexelMode::Landscape => "landscape",
         PexelMode::Portrait => "portrait"
     };
-    let url = shit["photos"][i]["src"][mode].as_str().unwrap();
-    let data = reqwest::blocking::get(url).unwrap().bytes().unwrap();
-    let img = image::load_from_memory(&data).unwrap();
-    img
+    let url = shit["photos"][i]["src"][mode]
+        .as_str()
+        .ok_or_else(|| LimageError::Network(format!("image for query '{query}' has no {mode} url")))?;
+    let data = reqwest::blocking::get(url)?.bytes()?;
+    Ok(image::load_from_memory(&data)?)
 }
 
 pub trait ToLimage {
     fn to_limage_rgb(self) -> LimageRgb;
     fn to_limage_rgba(self) -> LimageRgba;
+    fn to_limage_luma(self) -> LimageLuma;
+    fn to_limage_rgb16(self) -> LimageRgb16;
+    fn to_limage_rgba16(self) -> LimageRgba16;
+    fn to_limage_rgb32f(self) -> LimageRgb32F;
 }
 
 impl ToLimage for DynamicImage {
@@ -282,4 +1070,16 @@ impl ToLimage for DynamicImage {
     fn to_limage_rgba(self) -> LimageRgba {
         LimageRgba { imgbuff: self.into_rgba8() }
     }
+    fn to_limage_luma(self) -> LimageLuma {
+        LimageLuma { imgbuff: self.into_luma8() }
+    }
+    fn to_limage_rgb16(self) -> LimageRgb16 {
+        LimageRgb16 { imgbuff: self.into_rgb16() }
+    }
+    fn to_limage_rgba16(self) -> LimageRgba16 {
+        LimageRgba16 { imgbuff: self.into_rgba16() }
+    }
+    fn to_limage_rgb32f(self) -> LimageRgb32F {
+        LimageRgb32F { imgbuff: self.into_rgb32f() }
+    }
 }
\ No newline at end of file
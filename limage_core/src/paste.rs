@@ -0,0 +1,80 @@
+use crate::{BlendMode, LimageLuma};
+
+// the point both images get lined up on, the paste position is an offset from it.
+// `TopLeft` with a position is a plain paste, `BottomRight` with (-10, -10) sits 10 pixels in
+// from the bottom right corner
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Anchor {
+    #[default]
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    // how far along each axis the anchor sits, 0 is the left/top edge and 2 the right/bottom one
+    fn halves(self) -> (i32, i32) {
+        match self {
+            Anchor::TopLeft => (0, 0),
+            Anchor::Top => (1, 0),
+            Anchor::TopRight => (2, 0),
+            Anchor::Left => (0, 1),
+            Anchor::Center => (1, 1),
+            Anchor::Right => (2, 1),
+            Anchor::BottomLeft => (0, 2),
+            Anchor::Bottom => (1, 2),
+            Anchor::BottomRight => (2, 2),
+        }
+    }
+
+    // top left corner of an `inner` sized box placed inside `outer`
+    pub fn place(self, outer: (u32, u32), inner: (u32, u32), offset: (i32, i32)) -> (i32, i32) {
+        let (hx, hy) = self.halves();
+        let x = (outer.0 as i32 - inner.0 as i32) * hx / 2;
+        let y = (outer.1 as i32 - inner.1 as i32) * hy / 2;
+        (x + offset.0, y + offset.1)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct PasteOptions<'a> {
+    pub anchor: Anchor,
+    pub opacity: f32,
+    pub mode: BlendMode,
+    // in the pasted image's coordinates, 0 hides a pixel and 255 keeps it
+    pub mask: Option<&'a LimageLuma>,
+}
+
+impl Default for PasteOptions<'_> {
+    fn default() -> Self {
+        PasteOptions { anchor: Anchor::TopLeft, opacity: 1.0, mode: BlendMode::Normal, mask: None }
+    }
+}
+
+impl<'a> PasteOptions<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn with_anchor(mut self, anchor: Anchor) -> Self {
+        self.anchor = anchor;
+        self
+    }
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity;
+        self
+    }
+    pub fn with_mode(mut self, mode: BlendMode) -> Self {
+        self.mode = mode;
+        self
+    }
+    pub fn with_mask(mut self, mask: &'a LimageLuma) -> Self {
+        self.mask = Some(mask);
+        self
+    }
+}
@@ -0,0 +1,394 @@
+use std::collections::HashMap;
+
+use crate::Limage;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Quantizer {
+    MedianCut,
+    // seeded with the median cut palette so the result is deterministic
+    KMeans { iterations: u32 },
+    Octree,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Dither {
+    #[default]
+    None,
+    FloydSteinberg,
+    Atkinson,
+    // ordered dithering, `size` is the side of the threshold matrix and gets rounded up to a power of two
+    Bayer { size: u32 },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Palette {
+    colors: Vec<[u8; 3]>,
+}
+
+impl Palette {
+    pub fn new(colors: Vec<[u8; 3]>) -> Self {
+        Palette { colors }
+    }
+
+    // builds a palette of at most `size` colors, fully transparent pixels are ignored
+    pub fn from_image<L: Limage + ?Sized>(img: &L, size: usize, quantizer: Quantizer) -> Self {
+        let histogram = histogram(img);
+        let colors = if histogram.len() <= size {
+            histogram.into_iter().map(|(c, _)| c).collect()
+        } else {
+            match quantizer {
+                Quantizer::MedianCut => median_cut(&histogram, size),
+                Quantizer::KMeans { iterations } => k_means(&histogram, median_cut(&histogram, size), iterations),
+                Quantizer::Octree => octree(&histogram, size),
+            }
+        };
+        Palette { colors }
+    }
+
+    pub fn colors(&self) -> &[[u8; 3]] {
+        &self.colors
+    }
+
+    pub fn len(&self) -> usize {
+        self.colors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.colors.is_empty()
+    }
+
+    pub fn nearest_index(&self, color: [u8; 3]) -> Option<usize> {
+        let c = color.map(|c| c as f32);
+        self.nearest_index_f32(c)
+    }
+
+    pub fn nearest(&self, color: [u8; 3]) -> Option<[u8; 3]> {
+        self.nearest_index(color).map(|i| self.colors[i])
+    }
+
+    fn nearest_index_f32(&self, c: [f32; 3]) -> Option<usize> {
+        self.colors
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (i, distance2(c, p.map(|p| p as f32))))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
+    }
+}
+
+fn distance2(a: [f32; 3], b: [f32; 3]) -> f32 {
+    (0..3).map(|i| (a[i] - b[i]).powi(2)).sum()
+}
+
+// unique colors with their pixel counts, sorted so every quantizer sees them in the same order
+fn histogram<L: Limage + ?Sized>(img: &L) -> Vec<([u8; 3], u64)> {
+    let mut counts: HashMap<[u8; 3], u64> = HashMap::new();
+    for y in 0..img.height() as i32 {
+        for x in 0..img.width() as i32 {
+            if let Some([r, g, b, a]) = img.get_rgba((x, y)) {
+                if a > 0 {
+                    *counts.entry([r, g, b]).or_insert(0) += 1;
+                }
+            }
+        }
+    }
+    let mut histogram: Vec<_> = counts.into_iter().collect();
+    histogram.sort_unstable();
+    histogram
+}
+
+fn mean(colors: &[([u8; 3], u64)]) -> [u8; 3] {
+    let mut sum = [0u64; 3];
+    let mut total = 0;
+    for &(c, n) in colors {
+        for i in 0..3 {
+            sum[i] += c[i] as u64 * n;
+        }
+        total += n;
+    }
+    sum.map(|s| (s as f64 / total.max(1) as f64).round() as u8)
+}
+
+fn median_cut(histogram: &[([u8; 3], u64)], size: usize) -> Vec<[u8; 3]> {
+    let mut boxes = vec![histogram.to_vec()];
+    while boxes.len() < size {
+        // split the box with the widest channel range
+        let widest = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| b.len() > 1)
+            .map(|(i, b)| {
+                let range = (0..3)
+                    .map(|ch| {
+                        let (lo, hi) = b.iter().fold((255, 0), |(lo, hi), (c, _)| (c[ch].min(lo), c[ch].max(hi)));
+                        (hi - lo, ch)
+                    })
+                    .max()
+                    .unwrap();
+                (range, i)
+            })
+            .max();
+        let ((_, ch), i) = match widest {
+            Some(w) => w,
+            None => break,
+        };
+        let mut b = boxes.swap_remove(i);
+        b.sort_unstable_by_key(|(c, _)| c[ch]);
+        // weighted median, but always leave at least one color on each side
+        let total: u64 = b.iter().map(|(_, n)| n).sum();
+        let mut acc = 0;
+        let mut split = 1;
+        for (j, (_, n)) in b.iter().enumerate() {
+            acc += n;
+            if acc * 2 >= total {
+                split = j + 1;
+                break;
+            }
+        }
+        let split = split.clamp(1, b.len() - 1);
+        let rest = b.split_off(split);
+        boxes.push(b);
+        boxes.push(rest);
+    }
+    boxes.iter().map(|b| mean(b)).collect()
+}
+
+fn k_means(histogram: &[([u8; 3], u64)], seed: Vec<[u8; 3]>, iterations: u32) -> Vec<[u8; 3]> {
+    let mut centers: Vec<[f32; 3]> = seed.iter().map(|c| c.map(|c| c as f32)).collect();
+    for _ in 0..iterations {
+        let mut sums = vec![([0.0f64; 3], 0u64); centers.len()];
+        for &(c, n) in histogram {
+            let c = c.map(|c| c as f32);
+            let nearest = (0..centers.len())
+                .min_by(|&a, &b| distance2(c, centers[a]).total_cmp(&distance2(c, centers[b])))
+                .unwrap();
+            for (s, c) in sums[nearest].0.iter_mut().zip(c) {
+                *s += c as f64 * n as f64;
+            }
+            sums[nearest].1 += n;
+        }
+        let mut moved = false;
+        for (center, (sum, n)) in centers.iter_mut().zip(sums) {
+            // an empty cluster keeps its old center
+            if n == 0 {
+                continue;
+            }
+            let next = sum.map(|s| (s / n as f64) as f32);
+            moved |= distance2(*center, next) > 0.01;
+            *center = next;
+        }
+        if !moved {
+            break;
+        }
+    }
+    centers.iter().map(|c| c.map(|c| c.round() as u8)).collect()
+}
+
+#[derive(Clone, Default)]
+struct OctreeNode {
+    // totals of every color below this node, so folding it into a leaf is just dropping its children
+    sum: [u64; 3],
+    count: u64,
+    // 0 means no child, the root is never anyone's child
+    children: [usize; 8],
+}
+
+fn octree(histogram: &[([u8; 3], u64)], size: usize) -> Vec<[u8; 3]> {
+    let mut nodes = vec![OctreeNode::default()];
+    // inner nodes by depth, so the deepest ones get folded first
+    let mut levels: Vec<Vec<usize>> = vec![vec![]; 8];
+    levels[0].push(0);
+    let mut leaves = 0;
+
+    for &(c, n) in histogram {
+        let mut node = 0;
+        for depth in 0..=8 {
+            for (s, c) in nodes[node].sum.iter_mut().zip(c) {
+                *s += c as u64 * n;
+            }
+            nodes[node].count += n;
+            if depth == 8 {
+                break;
+            }
+            let bit = 7 - depth;
+            let octant = ((c[0] >> bit & 1) << 2 | (c[1] >> bit & 1) << 1 | (c[2] >> bit & 1)) as usize;
+            if nodes[node].children[octant] == 0 {
+                nodes.push(OctreeNode::default());
+                let child = nodes.len() - 1;
+                nodes[node].children[octant] = child;
+                if depth < 7 {
+                    levels[depth + 1].push(child);
+                } else {
+                    leaves += 1;
+                }
+            }
+            node = nodes[node].children[octant];
+        }
+    }
+
+    // within a level the node holding the fewest pixels gets folded first
+    for level in levels.iter_mut() {
+        level.sort_unstable_by_key(|&i| std::cmp::Reverse(nodes[i].count));
+    }
+    // deeper levels are gone by the time a node gets folded, so all its children are leaves
+    while leaves > size.max(1) {
+        let i = match levels.iter_mut().rev().find_map(|l| l.pop()) {
+            Some(i) => i,
+            None => break,
+        };
+        let mut octants: Vec<usize> = (0..8).filter(|&o| nodes[i].children[o] != 0).collect();
+        let excess = leaves - size.max(1);
+        if octants.len() - 1 <= excess {
+            nodes[i].children = [0; 8];
+            leaves -= octants.len() - 1;
+            continue;
+        }
+        // folding the whole node would leave too few colors, so only its smallest children get
+        // merged into one
+        octants.sort_unstable_by_key(|&o| nodes[nodes[i].children[o]].count);
+        let keep = nodes[i].children[octants[0]];
+        for &o in &octants[1..=excess] {
+            let child = std::mem::take(&mut nodes[i].children[o]);
+            let (sum, count) = (nodes[child].sum, nodes[child].count);
+            for (s, c) in nodes[keep].sum.iter_mut().zip(sum) {
+                *s += c;
+            }
+            nodes[keep].count += count;
+        }
+        leaves -= excess;
+    }
+
+    let mut colors = vec![];
+    let mut stack = vec![0];
+    while let Some(i) = stack.pop() {
+        let node = &nodes[i];
+        if node.children == [0; 8] {
+            colors.push(node.sum.map(|s| (s as f64 / node.count as f64).round() as u8));
+        } else {
+            stack.extend(node.children.iter().rev().filter(|&&c| c != 0));
+        }
+    }
+    colors
+}
+
+const FLOYD_STEINBERG: [(i32, i32, f32); 4] = [(1, 0, 7.0 / 16.0), (-1, 1, 3.0 / 16.0), (0, 1, 5.0 / 16.0), (1, 1, 1.0 / 16.0)];
+// atkinson only spreads 6/8 of the error, which keeps highlights and shadows clean
+const ATKINSON: [(i32, i32, f32); 6] = [(1, 0, 0.125), (2, 0, 0.125), (-1, 1, 0.125), (0, 1, 0.125), (1, 1, 0.125), (0, 2, 0.125)];
+
+fn bayer_matrix(size: u32) -> (usize, Vec<f32>) {
+    let mut n = 1;
+    let mut m = vec![0u32];
+    while n < size.clamp(2, 64) as usize {
+        let mut next = vec![0; n * n * 4];
+        for y in 0..n {
+            for x in 0..n {
+                let v = m[y * n + x] * 4;
+                next[y * 2 * n + x] = v;
+                next[y * 2 * n + x + n] = v + 2;
+                next[(y + n) * 2 * n + x] = v + 3;
+                next[(y + n) * 2 * n + x + n] = v + 1;
+            }
+        }
+        n *= 2;
+        m = next;
+    }
+    let cells = (n * n) as f32;
+    (n, m.into_iter().map(|v| (v as f32 + 0.5) / cells - 0.5).collect())
+}
+
+// the remapped rgb of every pixel in row-major order. fully transparent pixels keep their color
+// and take no part in error diffusion
+pub(crate) fn remap<L: Limage + ?Sized>(img: &L, palette: &Palette, dither: Dither) -> Vec<[u8; 3]> {
+    let (w, h) = (img.width() as i32, img.height() as i32);
+    let mut out = Vec::with_capacity((w * h) as usize);
+    let mut error = vec![[0.0f32; 3]; (w * h) as usize];
+    let bayer = match dither {
+        Dither::Bayer { size } => Some(bayer_matrix(size)),
+        _ => None,
+    };
+    // roughly the spacing between palette colors if they were spread evenly over the rgb cube
+    let spread = 255.0 / (palette.len().max(1) as f32).cbrt();
+    let kernel: &[(i32, i32, f32)] = match dither {
+        Dither::FloydSteinberg => &FLOYD_STEINBERG,
+        Dither::Atkinson => &ATKINSON,
+        _ => &[],
+    };
+
+    for y in 0..h {
+        for x in 0..w {
+            let [r, g, b, a] = img.get_rgba((x, y)).unwrap_or([0; 4]);
+            if a == 0 || palette.is_empty() {
+                out.push([r, g, b]);
+                continue;
+            }
+            let i = (y * w + x) as usize;
+            let mut c = [r, g, b].map(|c| c as f32);
+            for k in 0..3 {
+                c[k] = (c[k] + error[i][k]).clamp(0.0, 255.0);
+            }
+            if let Some((n, m)) = &bayer {
+                let t = m[(y as usize % n) * n + x as usize % n] * spread;
+                c = c.map(|c| c + t);
+            }
+            let chosen = palette.colors[palette.nearest_index_f32(c).unwrap()];
+            for &(dx, dy, weight) in kernel {
+                let (nx, ny) = (x + dx, y + dy);
+                if nx < 0 || nx >= w || ny >= h {
+                    continue;
+                }
+                let j = (ny * w + nx) as usize;
+                for k in 0..3 {
+                    error[j][k] += (c[k] - chosen[k] as f32) * weight;
+                }
+            }
+            out.push(chosen);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LimageRgb;
+
+    const QUANTIZERS: [Quantizer; 3] = [Quantizer::MedianCut, Quantizer::KMeans { iterations: 4 }, Quantizer::Octree];
+
+    fn gradient() -> LimageRgb {
+        let mut img = LimageRgb::new(64, 64);
+        for y in 0..64 {
+            for x in 0..64 {
+                img.put_rgb((x, y), [x as u8 * 4, y as u8 * 4, (x + y) as u8 * 2]);
+            }
+        }
+        img
+    }
+
+    #[test]
+    fn palette_has_the_requested_size() {
+        let mut few = LimageRgb::new(4, 4).with_color([10, 20, 30]);
+        few.put_rgb((0, 0), [200, 0, 0]);
+        few.put_rgb((1, 0), [0, 200, 0]);
+        for img in [gradient(), few] {
+            let unique = histogram(&img).len();
+            for quantizer in QUANTIZERS {
+                for size in [1, 2, 3, 4, 5, 7, 8, 9, 16, 63, 256] {
+                    let palette = Palette::from_image(&img, size, quantizer);
+                    assert_eq!(palette.len(), size.min(unique), "{quantizer:?} with {size} colors");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn quantize_keeps_every_color_of_the_palette() {
+        for quantizer in QUANTIZERS {
+            let mut img = gradient();
+            let palette = img.quantize(4, quantizer, Dither::FloydSteinberg);
+            let used = histogram(&img);
+            assert_eq!(palette.len(), 4, "{quantizer:?}");
+            assert!(used.len() > 1, "{quantizer:?} flattened the image");
+            assert!(used.iter().all(|(c, _)| palette.colors().contains(c)), "{quantizer:?}");
+        }
+    }
+}
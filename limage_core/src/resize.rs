@@ -0,0 +1,64 @@
+use image::{imageops::{self, FilterType}, ImageBuffer, Pixel};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ResizeMode {
+    // stretches to exactly the requested size
+    #[default]
+    Exact,
+    // keeps the aspect ratio, the result may be smaller than requested on one side
+    Fit,
+    // keeps the aspect ratio and fills the whole size, cropping the overflow around the center
+    Cover,
+    // like `Fit`, centered on an image of the requested size filled with this color
+    Letterbox([u8; 4]),
+}
+
+fn scaled(width: u32, height: u32, scale: f64) -> (u32, u32) {
+    let w = (width as f64 * scale).round().max(1.0) as u32;
+    let h = (height as f64 * scale).round().max(1.0) as u32;
+    (w, h)
+}
+
+// `fill` turns the letterbox color into the buffer's pixel type
+pub(crate) fn resize_with<P, F>(
+    buf: &ImageBuffer<P, Vec<P::Subpixel>>,
+    width: u32,
+    height: u32,
+    filter: FilterType,
+    mode: ResizeMode,
+    fill: F,
+) -> ImageBuffer<P, Vec<P::Subpixel>>
+where
+    P: Pixel + 'static,
+    F: Fn([u8; 4]) -> P,
+{
+    let (src_w, src_h) = buf.dimensions();
+    if src_w == 0 || src_h == 0 || width == 0 || height == 0 {
+        return match mode {
+            ResizeMode::Letterbox(color) => ImageBuffer::from_pixel(width, height, fill(color)),
+            _ => ImageBuffer::new(width, height),
+        };
+    }
+    let (sx, sy) = (width as f64 / src_w as f64, height as f64 / src_h as f64);
+    match mode {
+        ResizeMode::Exact => imageops::resize(buf, width, height, filter),
+        ResizeMode::Fit => {
+            let (w, h) = scaled(src_w, src_h, sx.min(sy));
+            imageops::resize(buf, w.min(width), h.min(height), filter)
+        }
+        ResizeMode::Cover => {
+            let (w, h) = scaled(src_w, src_h, sx.max(sy));
+            let (w, h) = (w.max(width), h.max(height));
+            let resized = imageops::resize(buf, w, h, filter);
+            imageops::crop_imm(&resized, (w - width) / 2, (h - height) / 2, width, height).to_image()
+        }
+        ResizeMode::Letterbox(color) => {
+            let (w, h) = scaled(src_w, src_h, sx.min(sy));
+            let (w, h) = (w.min(width), h.min(height));
+            let resized = imageops::resize(buf, w, h, filter);
+            let mut out = ImageBuffer::from_pixel(width, height, fill(color));
+            imageops::replace(&mut out, &resized, ((width - w) / 2) as i64, ((height - h) / 2) as i64);
+            out
+        }
+    }
+}
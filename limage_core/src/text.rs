@@ -0,0 +1,230 @@
+use crate::{sizeof_text, FontHandle, Limage, TextScale};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum TextAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+    // stretches the gaps between words so every line but a paragraph's last fills the width
+    Justify,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum VerticalAlign {
+    #[default]
+    Top,
+    Middle,
+    Bottom,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TextLayout {
+    pub scale: TextScale,
+    pub align: TextAlign,
+    pub vertical_align: VerticalAlign,
+    // distance between baselines as a multiple of `scale.y`
+    pub line_height: f32,
+    // when set, the scale is reduced until `scale.y` is down to this or the text fits the box
+    pub min_size: Option<f32>,
+}
+
+// a piece of text drawn at one spot, `position` is the top left like `write_text`
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TextRun {
+    pub text: String,
+    pub position: (i32, i32),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextBlock {
+    pub runs: Vec<TextRun>,
+    pub lines: usize,
+    // the scale the text ended up with, smaller than asked for if it was shrunk to fit
+    pub scale: TextScale,
+    // inclusive, like the rectangle corners everywhere else. a block without any visible text
+    // gets the box's top left twice
+    pub bounds: ((i32, i32), (i32, i32)),
+}
+
+impl TextLayout {
+    pub fn new(scale: impl Into<TextScale>) -> Self {
+        TextLayout {
+            scale: scale.into(),
+            align: TextAlign::Left,
+            vertical_align: VerticalAlign::Top,
+            line_height: 1.2,
+            min_size: None,
+        }
+    }
+    pub fn with_align(mut self, align: TextAlign) -> Self {
+        self.align = align;
+        self
+    }
+    pub fn with_vertical_align(mut self, vertical_align: VerticalAlign) -> Self {
+        self.vertical_align = vertical_align;
+        self
+    }
+    pub fn with_line_height(mut self, line_height: f32) -> Self {
+        self.line_height = line_height;
+        self
+    }
+    pub fn with_shrink_to_fit(mut self, min_size: f32) -> Self {
+        self.min_size = Some(min_size);
+        self
+    }
+
+    // lays `text` out in the box at `position`. lines wrap at spaces, words too long for a line
+    // of their own are broken between characters, and '\n' starts a new paragraph
+    pub fn layout(&self, text: &str, font: &FontHandle, position: (i32, i32), size: (u32, u32)) -> TextBlock {
+        // shrinking keeps the ratio between x and y
+        let at = |s: f32| self.scale.scaled(s / self.scale.y);
+        let fits = |s: f32| {
+            let lines = wrap(text, at(s), font, size.0 as i32);
+            let height = block_height(lines.len(), s, self.line_height);
+            height <= size.1 as i32 && lines.iter().all(|l| l.width <= size.0 as i32)
+        };
+        let mut text_size = self.scale.y;
+        if let Some(min_size) = self.min_size {
+            if !fits(text_size) {
+                // binary search for the largest size that still fits, down to half a pixel
+                let (mut lo, mut hi) = (min_size.min(self.scale.y), self.scale.y);
+                while hi - lo > 0.5 {
+                    let mid = (lo + hi) / 2.0;
+                    if fits(mid) {
+                        lo = mid;
+                    } else {
+                        hi = mid;
+                    }
+                }
+                text_size = lo;
+            }
+        }
+        self.place(text, font, position, size, at(text_size))
+    }
+
+    fn place(&self, text: &str, font: &FontHandle, position: (i32, i32), size: (u32, u32), scale: TextScale) -> TextBlock {
+        let (box_w, box_h) = (size.0 as i32, size.1 as i32);
+        let lines = wrap(text, scale, font, box_w);
+        let height = block_height(lines.len(), scale.y, self.line_height);
+        let top = position.1
+            + match self.vertical_align {
+                VerticalAlign::Top => 0,
+                VerticalAlign::Middle => (box_h - height) / 2,
+                VerticalAlign::Bottom => box_h - height,
+            };
+        let advance = scale.y * self.line_height;
+
+        let mut runs = vec![];
+        for (i, line) in lines.iter().enumerate() {
+            let y = top + (i as f32 * advance).round() as i32;
+            let justify = self.align == TextAlign::Justify && !line.last_of_paragraph && line.words.len() > 1;
+            if justify {
+                let widths: Vec<i32> = line.words.iter().map(|w| sizeof_text(w, scale, font).0).collect();
+                let gap = (box_w - widths.iter().sum::<i32>()) as f32 / (line.words.len() - 1) as f32;
+                let mut x = position.0 as f32;
+                for (word, w) in line.words.iter().zip(widths) {
+                    runs.push(TextRun { text: word.clone(), position: (x.round() as i32, y) });
+                    x += w as f32 + gap;
+                }
+            } else {
+                let x = position.0
+                    + match self.align {
+                        TextAlign::Left | TextAlign::Justify => 0,
+                        TextAlign::Center => (box_w - line.width) / 2,
+                        TextAlign::Right => box_w - line.width,
+                    };
+                if !line.words.is_empty() {
+                    runs.push(TextRun { text: line.words.join(" "), position: (x, y) });
+                }
+            }
+        }
+
+        let mut bounds: Option<((i32, i32), (i32, i32))> = None;
+        for run in &runs {
+            let (w, h) = sizeof_text(&run.text, scale, font);
+            if w <= 0 || h <= 0 {
+                continue;
+            }
+            let (x, y) = run.position;
+            bounds = Some(match bounds {
+                Some((min, max)) => ((min.0.min(x), min.1.min(y)), (max.0.max(x + w - 1), max.1.max(y + h - 1))),
+                None => ((x, y), (x + w - 1, y + h - 1)),
+            });
+        }
+        TextBlock {
+            runs,
+            lines: lines.len(),
+            scale,
+            bounds: bounds.unwrap_or((position, position)),
+        }
+    }
+}
+
+struct Line {
+    words: Vec<String>,
+    width: i32,
+    last_of_paragraph: bool,
+}
+
+fn block_height(lines: usize, size: f32, line_height: f32) -> i32 {
+    if lines == 0 {
+        return 0;
+    }
+    ((lines - 1) as f32 * size * line_height + size).round() as i32
+}
+
+fn wrap(text: &str, scale: TextScale, font: &FontHandle, max_width: i32) -> Vec<Line> {
+    let width = |s: &str| sizeof_text(s, scale, font).0;
+    let mut lines = vec![];
+    for paragraph in text.split('\n') {
+        let mut words: Vec<String> = vec![];
+        for word in paragraph.split_whitespace() {
+            let candidate = if words.is_empty() { word.to_string() } else { format!("{} {word}", words.join(" ")) };
+            if width(&candidate) <= max_width {
+                words.push(word.to_string());
+                continue;
+            }
+            if !words.is_empty() {
+                let w = width(&words.join(" "));
+                lines.push(Line { words: std::mem::take(&mut words), width: w, last_of_paragraph: false });
+            }
+            // a word wider than the whole line gets split wherever it overflows
+            let mut piece = String::new();
+            for c in word.chars() {
+                piece.push(c);
+                if piece.chars().count() > 1 && width(&piece) > max_width {
+                    piece.pop();
+                    let w = width(&piece);
+                    lines.push(Line { words: vec![std::mem::take(&mut piece)], width: w, last_of_paragraph: false });
+                    piece.push(c);
+                }
+            }
+            words.push(piece);
+        }
+        let w = width(&words.join(" "));
+        lines.push(Line { words, width: w, last_of_paragraph: true });
+    }
+    lines
+}
+
+// `pos` is the top left of the line box, the baseline sits `ascent` below it
+pub(crate) fn draw_text<L: Limage + ?Sized>(img: &mut L, pos: (i32, i32), color: [u8; 4], text: &str, scale: TextScale, font: &FontHandle) {
+    let ascent = font.line_metrics(scale).ascent;
+    draw_text_baseline(img, (pos.0 as f32, pos.1 as f32 + ascent), color, text, scale, font);
+}
+
+// `origin` is the left end of the baseline and may fall between pixels
+pub(crate) fn draw_text_baseline<L: Limage + ?Sized>(img: &mut L, origin: (f32, f32), color: [u8; 4], text: &str, scale: TextScale, font: &FontHandle) {
+    for glyph in font.glyphs(text, scale, origin) {
+        if let Some(bb) = glyph.pixel_bounding_box() {
+            glyph.draw(|x, y, coverage| {
+                let p = (bb.min.x + x as i32, bb.min.y + y as i32);
+                let a = (color[3] as f32 * coverage.clamp(0.0, 1.0)).round() as u8;
+                if a > 0 {
+                    img.blend_rgba(p, [color[0], color[1], color[2], a]);
+                }
+            });
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use crate::Limage;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Sampling {
+    Nearest,
+    #[default]
+    Bilinear,
+    Bicubic,
+}
+
+// 2x3 matrix mapping (x, y) to (m[0][0] x + m[0][1] y + m[0][2], m[1][0] x + m[1][1] y + m[1][2])
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Affine {
+    pub m: [[f32; 3]; 2],
+}
+
+impl Default for Affine {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Affine {
+    pub fn new(m: [[f32; 3]; 2]) -> Self {
+        Affine { m }
+    }
+    pub fn identity() -> Self {
+        Affine { m: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0]] }
+    }
+    pub fn translate(dx: f32, dy: f32) -> Self {
+        Affine { m: [[1.0, 0.0, dx], [0.0, 1.0, dy]] }
+    }
+    pub fn scale(sx: f32, sy: f32) -> Self {
+        Affine { m: [[sx, 0.0, 0.0], [0.0, sy, 0.0]] }
+    }
+    // radians, clockwise on screen like everything else
+    pub fn rotate(angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Affine { m: [[cos, -sin, 0.0], [sin, cos, 0.0]] }
+    }
+    pub fn shear(kx: f32, ky: f32) -> Self {
+        Affine { m: [[1.0, kx, 0.0], [ky, 1.0, 0.0]] }
+    }
+
+    // `self` first, then `next`
+    pub fn then(self, next: Affine) -> Self {
+        let (a, b) = (next.m, self.m);
+        let mut m = [[0.0; 3]; 2];
+        for (row, out) in m.iter_mut().enumerate() {
+            for (col, v) in out.iter_mut().enumerate() {
+                *v = a[row][0] * b[0][col] + a[row][1] * b[1][col];
+            }
+            out[2] += a[row][2];
+        }
+        Affine { m }
+    }
+
+    pub fn inverse(self) -> Option<Self> {
+        let [[a, b, c], [d, e, f]] = self.m;
+        let det = a * e - b * d;
+        if det.abs() < 1e-12 {
+            return None;
+        }
+        let (ia, ib, id, ie) = (e / det, -b / det, -d / det, a / det);
+        Some(Affine { m: [[ia, ib, -(ia * c + ib * f)], [id, ie, -(id * c + ie * f)]] })
+    }
+
+    pub fn apply(&self, p: (f32, f32)) -> (f32, f32) {
+        let [[a, b, c], [d, e, f]] = self.m;
+        (a * p.0 + b * p.1 + c, d * p.0 + e * p.1 + f)
+    }
+}
+
+fn premultiplied<L: Limage + ?Sized>(img: &L, x: i32, y: i32) -> [f32; 4] {
+    match img.get_rgba_f32((x, y)) {
+        Some([r, g, b, a]) => [r * a, g * a, b * a, a],
+        None => [0.0; 4],
+    }
+}
+
+// catmull-rom weights
+fn cubic(t: f32) -> [f32; 4] {
+    let (t2, t3) = (t * t, t * t * t);
+    [
+        (-t3 + 2.0 * t2 - t) / 2.0,
+        (3.0 * t3 - 5.0 * t2 + 2.0) / 2.0,
+        (-3.0 * t3 + 4.0 * t2 + t) / 2.0,
+        (t3 - t2) / 2.0,
+    ]
+}
+
+// samples at a position in pixel-center coordinates at the image's own precision, outside the
+// image is transparent
+pub(crate) fn sample<L: Limage + ?Sized>(img: &L, p: (f32, f32), sampling: Sampling) -> [f32; 4] {
+    let acc = match sampling {
+        Sampling::Nearest => premultiplied(img, p.0.round() as i32, p.1.round() as i32),
+        Sampling::Bilinear => {
+            let (x0, y0) = (p.0.floor(), p.1.floor());
+            let (tx, ty) = (p.0 - x0, p.1 - y0);
+            let (x0, y0) = (x0 as i32, y0 as i32);
+            let mut acc = [0.0; 4];
+            for (dy, wy) in [(0, 1.0 - ty), (1, ty)] {
+                for (dx, wx) in [(0, 1.0 - tx), (1, tx)] {
+                    let c = premultiplied(img, x0 + dx, y0 + dy);
+                    for (a, c) in acc.iter_mut().zip(c) {
+                        *a += c * wx * wy;
+                    }
+                }
+            }
+            acc
+        }
+        Sampling::Bicubic => {
+            let (x0, y0) = (p.0.floor(), p.1.floor());
+            let (wx, wy) = (cubic(p.0 - x0), cubic(p.1 - y0));
+            let (x0, y0) = (x0 as i32, y0 as i32);
+            let mut acc = [0.0; 4];
+            for (dy, wy) in (-1..=2).zip(wy) {
+                for (dx, wx) in (-1..=2).zip(wx) {
+                    let c = premultiplied(img, x0 + dx, y0 + dy);
+                    for (a, c) in acc.iter_mut().zip(c) {
+                        *a += c * wx * wy;
+                    }
+                }
+            }
+            acc
+        }
+    };
+    let a = acc[3].clamp(0.0, 1.0);
+    if a <= 0.0 {
+        return [0.0; 4];
+    }
+    [acc[0] / a, acc[1] / a, acc[2] / a, a]
+}
+
+// the pixels of a `width` x `height` image where each pixel is `matrix` applied to the source
+pub(crate) fn affine<L: Limage + ?Sized>(img: &L, width: u32, height: u32, matrix: Affine, sampling: Sampling) -> Vec<[f32; 4]> {
+    let mut out = Vec::with_capacity((width * height) as usize);
+    // a matrix that can't be inverted squashes the image into a line or a point, nothing to see
+    let inverse = match matrix.inverse() {
+        Some(inverse) => inverse,
+        None => return vec![[0.0; 4]; (width * height) as usize],
+    };
+    for y in 0..height {
+        for x in 0..width {
+            out.push(sample(img, inverse.apply((x as f32, y as f32)), sampling));
+        }
+    }
+    out
+}
+
+// a `width` x `height` image where every pixel is copied from `source(x, y)` of `img`, for the
+// transforms that only move pixels around
+pub(crate) fn rearrange<L, F>(img: &L, width: u32, height: u32, source: F) -> L::ImgType
+where
+    L: Limage + ?Sized,
+    F: Fn(i32, i32) -> (i32, i32),
+{
+    let mut out = L::new(width, height);
+    for y in 0..height as i32 {
+        for x in 0..width as i32 {
+            if let Some(color) = img.get_rgba_f32(source(x, y)) {
+                out.put_rgba_f32((x, y), color);
+            }
+        }
+    }
+    out
+}
+
+// inclusive corners in any order clipped to the image, as (x, y, width, height)
+pub(crate) fn crop_rect(width: u32, height: u32, p1: (i32, i32), p2: (i32, i32)) -> (u32, u32, u32, u32) {
+    let (x1, x2) = (p1.0.min(p2.0).max(0), p1.0.max(p2.0).min(width as i32 - 1));
+    let (y1, y2) = (p1.1.min(p2.1).max(0), p1.1.max(p2.1).min(height as i32 - 1));
+    if x1 > x2 || y1 > y2 {
+        return (0, 0, 0, 0);
+    }
+    (x1 as u32, y1 as u32, (x2 - x1 + 1) as u32, (y2 - y1 + 1) as u32)
+}
@@ -0,0 +1,194 @@
+use crate::{PathCircuit, ShapeError};
+
+// curves get flattened into a polyline until no piece strays further than this
+// many pixels from the real curve, then walked with `PathCircuit` so the pixels stay connected
+const TOLERANCE: f32 = 0.25;
+const MIN_DEPTH: u32 = 3;
+const MAX_DEPTH: u32 = 16;
+
+fn lerp(t: f32, p1: (f32, f32), p2: (f32, f32)) -> (f32, f32) {
+    (t * (p2.0 - p1.0) + p1.0, t * (p2.1 - p1.1) + p1.1)
+}
+
+// de casteljau evaluation of a bezier curve of any degree
+fn bezier_point(t: f32, points: &[(f32, f32)]) -> (f32, f32) {
+    let mut points = points.to_vec();
+    while points.len() > 1 {
+        points = points.windows(2).map(|w| lerp(t, w[0], w[1])).collect();
+    }
+    points[0]
+}
+
+fn flatten(eval: &dyn Fn(f32) -> (f32, f32), out: &mut Vec<(f32, f32)>) {
+    let start = eval(0.0);
+    out.push(start);
+    subdivide(eval, (0.0, start), (1.0, eval(1.0)), 0, out);
+}
+
+fn subdivide(eval: &dyn Fn(f32) -> (f32, f32), a: (f32, (f32, f32)), b: (f32, (f32, f32)), depth: u32, out: &mut Vec<(f32, f32)>) {
+    let t = (a.0 + b.0) / 2.0;
+    let mid = eval(t);
+    let chord_mid = lerp(0.5, a.1, b.1);
+    let (dx, dy) = (mid.0 - chord_mid.0, mid.1 - chord_mid.1);
+    if depth >= MAX_DEPTH || (depth >= MIN_DEPTH && dx * dx + dy * dy <= TOLERANCE * TOLERANCE) {
+        out.push(b.1);
+        return;
+    }
+    subdivide(eval, a, (t, mid), depth + 1, out);
+    subdivide(eval, (t, mid), b, depth + 1, out);
+}
+
+fn to_f32(points: &[(i32, i32)]) -> Vec<(f32, f32)> {
+    points.iter().map(|&(x, y)| (x as f32, y as f32)).collect()
+}
+
+// `start` is passed on its own so the path is never empty
+fn walk(start: (i32, i32), polyline: &[(f32, f32)]) -> PathCircuit {
+    let mut points = vec![start];
+    points.extend(polyline.iter().map(|&(x, y)| (x.round() as i32, y.round() as i32)));
+    points.dedup();
+    PathCircuit::through(start, &points[1..])
+}
+
+fn bezier_path(start: (i32, i32), points: &[(i32, i32)]) -> PathCircuit {
+    let points = to_f32(points);
+    let mut polyline = vec![];
+    flatten(&|t| bezier_point(t, &points), &mut polyline);
+    walk(start, &polyline)
+}
+
+fn check_len(points: &[(i32, i32)], what: &str) -> Result<(), ShapeError> {
+    if points.len() < 2 {
+        return Err(ShapeError::InvalidGeometry(format!("{what} needs at least two points, got {}", points.len())));
+    }
+    Ok(())
+}
+
+pub struct QuadBezier {
+    path: PathCircuit,
+}
+
+impl QuadBezier {
+    pub fn new(p1: (i32, i32), control: (i32, i32), p2: (i32, i32)) -> Self {
+        QuadBezier { path: bezier_path(p1, &[p1, control, p2]) }
+    }
+}
+
+impl Iterator for QuadBezier {
+    type Item = (i32, i32);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.path.next()
+    }
+}
+
+pub struct CubicBezier {
+    path: PathCircuit,
+}
+
+impl CubicBezier {
+    pub fn new(p1: (i32, i32), c1: (i32, i32), c2: (i32, i32), p2: (i32, i32)) -> Self {
+        CubicBezier { path: bezier_path(p1, &[p1, c1, c2, p2]) }
+    }
+}
+
+impl Iterator for CubicBezier {
+    type Item = (i32, i32);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.path.next()
+    }
+}
+
+// bezier of any degree, `points` are the start point, the control points and the end point
+pub struct Bezier {
+    path: PathCircuit,
+}
+
+impl Bezier {
+    pub fn new(points: &[(i32, i32)]) -> Result<Self, ShapeError> {
+        check_len(points, "bezier curve")?;
+        Ok(Bezier { path: bezier_path(points[0], points) })
+    }
+}
+
+impl Iterator for Bezier {
+    type Item = (i32, i32);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.path.next()
+    }
+}
+
+// uniform catmull-rom spline passing through every point
+pub struct CatmullRom {
+    path: PathCircuit,
+}
+
+impl CatmullRom {
+    pub fn new(points: &[(i32, i32)]) -> Result<Self, ShapeError> {
+        check_len(points, "catmull-rom spline")?;
+        let pts = to_f32(points);
+        let n = pts.len();
+        let mut polyline = vec![];
+        for i in 0..n - 1 {
+            let p0 = pts[i.saturating_sub(1)];
+            let (p1, p2) = (pts[i], pts[i + 1]);
+            let p3 = pts[(i + 2).min(n - 1)];
+            let segment = |t: f32| {
+                let (t2, t3) = (t * t, t * t * t);
+                let c = |a: f32, b: f32, c: f32, d: f32| {
+                    0.5 * (2.0 * b + (c - a) * t + (2.0 * a - 5.0 * b + 4.0 * c - d) * t2 + (3.0 * b - a - 3.0 * c + d) * t3)
+                };
+                (c(p0.0, p1.0, p2.0, p3.0), c(p0.1, p1.1, p2.1, p3.1))
+            };
+            flatten(&segment, &mut polyline);
+        }
+        Ok(CatmullRom { path: walk(points[0], &polyline) })
+    }
+}
+
+impl Iterator for CatmullRom {
+    type Item = (i32, i32);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.path.next()
+    }
+}
+
+// uniform cubic b-spline, smoother than catmull-rom but only passes through the end points
+pub struct BSpline {
+    path: PathCircuit,
+}
+
+impl BSpline {
+    pub fn new(points: &[(i32, i32)]) -> Result<Self, ShapeError> {
+        check_len(points, "b-spline")?;
+        let pts = to_f32(points);
+        // tripled end points pin the curve to them
+        let mut padded = vec![pts[0]; 2];
+        padded.extend(&pts);
+        padded.extend([pts[pts.len() - 1]; 2]);
+
+        let mut polyline = vec![];
+        for w in padded.windows(4) {
+            let segment = |t: f32| {
+                let (t2, t3) = (t * t, t * t * t);
+                let c = |a: f32, b: f32, c: f32, d: f32| {
+                    ((1.0 - t).powi(3) * a + (3.0 * t3 - 6.0 * t2 + 4.0) * b + (-3.0 * t3 + 3.0 * t2 + 3.0 * t + 1.0) * c + t3 * d) / 6.0
+                };
+                (c(w[0].0, w[1].0, w[2].0, w[3].0), c(w[0].1, w[1].1, w[2].1, w[3].1))
+            };
+            flatten(&segment, &mut polyline);
+        }
+        Ok(BSpline { path: walk(points[0], &polyline) })
+    }
+}
+
+impl Iterator for BSpline {
+    type Item = (i32, i32);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.path.next()
+    }
+}
@@ -0,0 +1,178 @@
+use std::f32::consts::TAU;
+
+pub struct Ellipse {
+    points: std::vec::IntoIter<(i32, i32)>,
+}
+
+impl Ellipse {
+    pub fn new(origin: (i32, i32), radii: (u32, u32)) -> Self {
+        let (rx, ry) = (radii.0 as i32, radii.1 as i32);
+        // sample along both axes so steep and flat parts of the outline stay connected
+        let mut quadrant = vec![];
+        for x in 0..=rx {
+            quadrant.push((x, half_extent(x, rx, ry)));
+        }
+        for y in 0..=ry {
+            quadrant.push((half_extent(y, ry, rx), y));
+        }
+        quadrant.sort_unstable();
+        quadrant.dedup();
+
+        let mut points = vec![];
+        for (x, y) in quadrant {
+            for (sx, sy) in [(1, 1), (-1, 1), (-1, -1), (1, -1)] {
+                if (sx < 0 && x == 0) || (sy < 0 && y == 0) {
+                    continue;
+                }
+                points.push((origin.0 + sx * x, origin.1 + sy * y));
+            }
+        }
+        Ellipse { points: points.into_iter() }
+    }
+}
+
+impl Iterator for Ellipse {
+    type Item = (i32, i32);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.points.next()
+    }
+}
+
+// the other coordinate of the ellipse at `t` along the axis with radius `r`
+fn half_extent(t: i32, r: i32, other: i32) -> i32 {
+    if r == 0 {
+        return other;
+    }
+    let t = t as f32 / r as f32;
+    (other as f32 * (1.0 - t * t).max(0.0).sqrt()).round() as i32
+}
+
+pub struct FilledEllipse {
+    origin: (i32, i32),
+    radii: (i32, i32),
+    y: i32,
+    x: i32,
+    x_end: i32,
+}
+
+impl FilledEllipse {
+    pub fn new(origin: (i32, i32), radii: (u32, u32)) -> Self {
+        let (rx, ry) = (radii.0 as i32, radii.1 as i32);
+        let half = half_extent(-ry, ry, rx);
+        FilledEllipse {
+            origin,
+            radii: (rx, ry),
+            y: -ry,
+            x: -half,
+            x_end: half,
+        }
+    }
+}
+
+impl Iterator for FilledEllipse {
+    type Item = (i32, i32);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (rx, ry) = self.radii;
+        if self.y > ry {
+            return None;
+        }
+        let p = (self.origin.0 + self.x, self.origin.1 + self.y);
+        if self.x == self.x_end {
+            self.y += 1;
+            let half = half_extent(self.y, ry, rx);
+            self.x = -half;
+            self.x_end = half;
+        } else {
+            self.x += 1;
+        }
+        Some(p)
+    }
+}
+
+// angles are in radians, measured from the positive x axis towards positive y,
+// which is clockwise on screen. the sweep goes from `start` to `end` in that direction
+// and leaves out `end` itself, so adjacent pie slices never overlap
+struct AngleRange {
+    start: f32,
+    sweep: f32,
+}
+
+impl AngleRange {
+    fn new(start: f32, end: f32) -> Self {
+        let sweep = match end - start {
+            d if d.abs() >= TAU => TAU,
+            d => d.rem_euclid(TAU),
+        };
+        AngleRange { start, sweep }
+    }
+
+    fn contains(&self, origin: (i32, i32), p: (i32, i32)) -> bool {
+        if self.sweep >= TAU {
+            return true;
+        }
+        // the center has no direction, it counts as angle 0 so exactly one of the slices making up
+        // a full turn gets it and an empty sweep gets nothing
+        let (dx, dy) = (p.0 - origin.0, p.1 - origin.1);
+        let angle = if (dx, dy) == (0, 0) { 0.0 } else { (dy as f32).atan2(dx as f32) };
+        (angle - self.start).rem_euclid(TAU) < self.sweep
+    }
+}
+
+pub struct Arc {
+    outline: Ellipse,
+    origin: (i32, i32),
+    range: AngleRange,
+}
+
+impl Arc {
+    pub fn new(origin: (i32, i32), radius: u32, start: f32, end: f32) -> Self {
+        Self::elliptical(origin, (radius, radius), start, end)
+    }
+    pub fn elliptical(origin: (i32, i32), radii: (u32, u32), start: f32, end: f32) -> Self {
+        Arc {
+            outline: Ellipse::new(origin, radii),
+            origin,
+            range: AngleRange::new(start, end),
+        }
+    }
+}
+
+impl Iterator for Arc {
+    type Item = (i32, i32);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (origin, range) = (self.origin, &self.range);
+        self.outline.find(|&p| range.contains(origin, p))
+    }
+}
+
+// filled wedge of a circle or ellipse, for pie charts and gauges
+pub struct Pie {
+    area: FilledEllipse,
+    origin: (i32, i32),
+    range: AngleRange,
+}
+
+impl Pie {
+    pub fn new(origin: (i32, i32), radius: u32, start: f32, end: f32) -> Self {
+        Self::elliptical(origin, (radius, radius), start, end)
+    }
+    pub fn elliptical(origin: (i32, i32), radii: (u32, u32), start: f32, end: f32) -> Self {
+        Pie {
+            area: FilledEllipse::new(origin, radii),
+            origin,
+            range: AngleRange::new(start, end),
+        }
+    }
+}
+
+impl Iterator for Pie {
+    type Item = (i32, i32);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (origin, range) = (self.origin, &self.range);
+        self.area.find(|&p| range.contains(origin, p))
+    }
+}
@@ -1,5 +1,38 @@
-use image::RgbaImage;
+use image::{ImageError, RgbaImage};
 use std::collections::VecDeque;
+use std::fmt;
+
+mod curve;
+mod ellipse;
+mod polygon;
+mod stroke;
+pub use curve::{BSpline, Bezier, CatmullRom, CubicBezier, QuadBezier};
+pub use ellipse::{Arc, Ellipse, FilledEllipse, Pie};
+pub use polygon::{FillRule, Polygon};
+pub use stroke::{LineCap, LineJoin, Stroke, StrokeStyle};
+
+#[derive(Debug)]
+pub enum ShapeError {
+    InvalidGeometry(String),
+    Image(ImageError),
+}
+
+impl fmt::Display for ShapeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShapeError::InvalidGeometry(e) => write!(f, "invalid geometry: {e}"),
+            ShapeError::Image(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for ShapeError {}
+
+impl From<ImageError> for ShapeError {
+    fn from(e: ImageError) -> Self {
+        ShapeError::Image(e)
+    }
+}
 
 pub struct Rectangle {
     pos_x: i32,
@@ -256,49 +289,49 @@ pub struct PathCircuit {
 }
 
 impl PathCircuit {
-    pub fn new_path(points: &Vec<(i32, i32)>) -> Self {
-        if points.is_empty() {
-            panic!("why the fuck would you input an empty list of points");
+    pub fn new_path(points: &Vec<(i32, i32)>) -> Result<Self, ShapeError> {
+        match points.split_first() {
+            Some((&start, rest)) => Ok(Self::through(start, rest)),
+            None => Err(ShapeError::InvalidGeometry("path needs at least one point".to_string())),
         }
+    }
+    // open path from `start` through `rest`, never empty so it can't fail
+    pub(crate) fn through(start: (i32, i32), rest: &[(i32, i32)]) -> Self {
+        let mut points = VecDeque::from(rest.to_vec());
+        points.push_front(start);
         if points.len() == 1 {
-            return PathCircuit {
-                points: VecDeque::from(vec![points[0], points[0]]),
-                last_point: None,
-                curr_line: Line::new(points[0], points[0]),
-                is_circuit: false,
-                start: (0, 0) // doesnt matter
-            };
+            points.push_back(start);
         }
-        return PathCircuit {
-            points: VecDeque::from(points.clone()),
+        PathCircuit {
+            curr_line: Line::new(start, points[1]),
+            points,
             last_point: None,
-            curr_line: Line::new(points[0], points[1]),
             is_circuit: false,
             start: (0, 0) // doesnt matter
-        };
+        }
     }
-    pub fn new_circuit(points: &Vec<(i32, i32)>) -> Self {
+    pub fn new_circuit(points: &Vec<(i32, i32)>) -> Result<Self, ShapeError> {
         if points.is_empty() {
-            panic!("why the fuck would you input an empty list of points");
+            return Err(ShapeError::InvalidGeometry("circuit needs at least one point".to_string()));
         }
         if points.len() == 1 {
-            return PathCircuit {
+            return Ok(PathCircuit {
                 points: VecDeque::from(vec![points[0], points[0]]),
                 last_point: None,
                 curr_line: Line::new(points[0], points[0]),
                 is_circuit: false,
                 start: (0, 0) // doesnt matter
-            };
+            });
         }
         let mut new_points = points.clone();
         new_points.push(points[0]);
-        return PathCircuit {
+        Ok(PathCircuit {
             points: VecDeque::from(new_points.clone()),
             last_point: None,
             curr_line: Line::new(new_points[0], new_points[1]),
             is_circuit: true,
-            start: points[0].clone()
-        };
+            start: points[0]
+        })
     }
 }
 
@@ -354,15 +387,15 @@ pub struct Text {
 }
 
 impl Text {
-    pub fn new(position: (i32, i32), text: &str, size: u32) -> Self {
-        Text {
+    pub fn new(position: (i32, i32), text: &str, size: u32) -> Result<Self, ShapeError> {
+        Ok(Text {
             position: position,
-            font_sheet: image::open("assets/font.png").unwrap().into_rgba8(),
+            font_sheet: image::open("assets/font.png")?.into_rgba8(),
             text: text.to_string(),
             size: size as i32,
             current_index: 0,
             current_box: Rectangle::new((0, 0), (6 * size as i32 - 1, 12 * size as i32 - 1))
-        }
+        })
     }
 }
 
@@ -420,14 +453,20 @@ pub struct WireFrame {
 }
 
 impl WireFrame {
-    pub fn new(vertex_table: &Vec<(i32, i32)>, edge_table: &Vec<(usize, usize)>) -> Self {
+    pub fn new(vertex_table: &Vec<(i32, i32)>, edge_table: &Vec<(usize, usize)>) -> Result<Self, ShapeError> {
         if edge_table.is_empty() {
-            panic!("no edges");
+            return Err(ShapeError::InvalidGeometry("wireframe needs at least one edge".to_string()));
+        }
+        if let Some(&(e1, e2)) = edge_table.iter().find(|&&(e1, e2)| e1.max(e2) >= vertex_table.len()) {
+            return Err(ShapeError::InvalidGeometry(format!(
+                "edge ({e1}, {e2}) refers to a missing vertex, only {} vertices given",
+                vertex_table.len()
+            )));
         }
         let (e1, e2) = edge_table[0];
-        WireFrame { vertex_table: vertex_table.clone(), edge_table: edge_table.clone(), curr_edge: 0, curr_line: Line::new(vertex_table[e1], vertex_table[e2]) }
+        Ok(WireFrame { vertex_table: vertex_table.clone(), edge_table: edge_table.clone(), curr_edge: 0, curr_line: Line::new(vertex_table[e1], vertex_table[e2]) })
     }
-    pub fn from_3d(vertex_table: &Vec<(f64, f64, f64)>, d: f64, edge_table: &Vec<(usize, usize)>, res: i32) -> Self {
+    pub fn from_3d(vertex_table: &Vec<(f64, f64, f64)>, d: f64, edge_table: &Vec<(usize, usize)>, res: i32) -> Result<Self, ShapeError> {
         let vertex_table_2d = vertex_table.iter()
             .map(|(x, y, z)| ((x / (d + z) * res as f64 * 0.5) as i32, (y / (d + z) * res as f64 * 0.5) as i32))
             .map(|(x, y)| (x + res / 2, y + res / 2))
@@ -455,3 +494,234 @@ impl Iterator for WireFrame {
         }
     }
 }
+
+// Xiaolin Wu line, yields every touched pixel together with its coverage in 0..1.
+// integer coordinates are pixel centers, same as `Line`
+pub struct LineAA {
+    p1: (f32, f32),
+    p2: (f32, f32),
+    steep: bool,
+    gradient: f32,
+    x: i32,
+    x_end: i32,
+    upper_half: bool,
+    finished: bool,
+}
+
+impl LineAA {
+    pub fn new(p1: (f32, f32), p2: (f32, f32)) -> Self {
+        let steep = (p2.1 - p1.1).abs() > (p2.0 - p1.0).abs();
+        let (mut p1, mut p2) = match steep {
+            true => ((p1.1, p1.0), (p2.1, p2.0)),
+            false => (p1, p2),
+        };
+        if p1.0 > p2.0 {
+            std::mem::swap(&mut p1, &mut p2);
+        }
+        let dx = p2.0 - p1.0;
+        let gradient = if dx == 0.0 { 0.0 } else { (p2.1 - p1.1) / dx };
+        LineAA {
+            p1,
+            p2,
+            steep,
+            gradient,
+            x: p1.0.round() as i32,
+            x_end: p2.0.round() as i32,
+            upper_half: true,
+            finished: false,
+        }
+    }
+
+    fn plot(&self, x: i32, y: i32) -> (i32, i32) {
+        match self.steep {
+            true => (y, x),
+            false => (x, y),
+        }
+    }
+}
+
+impl Iterator for LineAA {
+    type Item = ((i32, i32), f32);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.finished {
+                return None;
+            }
+            let x = self.x;
+            let (x1, x2) = (self.p1.0, self.p2.0);
+            // how much of this pixel column the line actually spans
+            let weight = if x1 == x2 {
+                1.0
+            } else {
+                ((x as f32 + 0.5).min(x2) - (x as f32 - 0.5).max(x1)).clamp(0.0, 1.0)
+            };
+            let y = self.p1.1 + self.gradient * (x as f32 - x1);
+            let y_floor = y.floor();
+            let fract = y - y_floor;
+
+            let item = if self.upper_half {
+                self.upper_half = false;
+                (self.plot(x, y_floor as i32), (1.0 - fract) * weight)
+            } else {
+                self.upper_half = true;
+                if self.x == self.x_end {
+                    self.finished = true;
+                }
+                self.x += 1;
+                (self.plot(x, y_floor as i32 + 1), fract * weight)
+            };
+            if item.1 > 0.0 {
+                return Some(item);
+            }
+        }
+    }
+}
+
+// every pixel of an inclusive box row by row, always stepping from the first corner to the second
+struct BoundingBox {
+    x1: i32,
+    x2: i32,
+    y2: i32,
+    x: i32,
+    y: i32,
+}
+
+impl BoundingBox {
+    fn new(p1: (i32, i32), p2: (i32, i32)) -> Self {
+        BoundingBox { x1: p1.0, x2: p2.0, y2: p2.1, x: p1.0, y: p1.1 }
+    }
+}
+
+impl Iterator for BoundingBox {
+    type Item = (i32, i32);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.y > self.y2 || self.x1 > self.x2 {
+            return None;
+        }
+        let p = (self.x, self.y);
+        if self.x == self.x2 {
+            self.x = self.x1;
+            self.y += 1;
+        } else {
+            self.x += 1;
+        }
+        Some(p)
+    }
+}
+
+// 1 pixel wide anti-aliased ring
+pub struct CircleAA {
+    bounds: BoundingBox,
+    origin: (f32, f32),
+    radius: f32,
+}
+
+impl CircleAA {
+    pub fn new(origin: (f32, f32), radius: f32) -> Self {
+        let r = radius.abs();
+        let (x1, y1) = ((origin.0 - r - 1.0).floor() as i32, (origin.1 - r - 1.0).floor() as i32);
+        let (x2, y2) = ((origin.0 + r + 1.0).ceil() as i32, (origin.1 + r + 1.0).ceil() as i32);
+        CircleAA {
+            bounds: BoundingBox::new((x1, y1), (x2, y2)),
+            origin,
+            radius: r,
+        }
+    }
+}
+
+impl Iterator for CircleAA {
+    type Item = ((i32, i32), f32);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let inner = (self.radius - 1.0).max(0.0);
+        let outer = self.radius + 1.0;
+        loop {
+            let coord = self.bounds.next()?;
+            let (dx, dy) = (coord.0 as f32 - self.origin.0, coord.1 as f32 - self.origin.1);
+            let d2 = dx * dx + dy * dy;
+            if d2 <= inner * inner || d2 >= outer * outer {
+                continue;
+            }
+            let coverage = 1.0 - (d2.sqrt() - self.radius).abs();
+            if coverage > 0.0 {
+                return Some((coord, coverage.min(1.0)));
+            }
+        }
+    }
+}
+
+// filled disc whose edge pixels carry partial coverage
+pub struct DiscAA {
+    bounds: BoundingBox,
+    origin: (f32, f32),
+    radius: f32,
+}
+
+impl DiscAA {
+    pub fn new(origin: (f32, f32), radius: f32) -> Self {
+        let r = radius.abs();
+        let (x1, y1) = ((origin.0 - r - 1.0).floor() as i32, (origin.1 - r - 1.0).floor() as i32);
+        let (x2, y2) = ((origin.0 + r + 1.0).ceil() as i32, (origin.1 + r + 1.0).ceil() as i32);
+        DiscAA {
+            bounds: BoundingBox::new((x1, y1), (x2, y2)),
+            origin,
+            radius: r,
+        }
+    }
+}
+
+impl Iterator for DiscAA {
+    type Item = ((i32, i32), f32);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let inner = (self.radius - 0.5).max(0.0);
+        let outer = self.radius + 0.5;
+        loop {
+            let coord = self.bounds.next()?;
+            let (dx, dy) = (coord.0 as f32 - self.origin.0, coord.1 as f32 - self.origin.1);
+            let d2 = dx * dx + dy * dy;
+            if d2 <= inner * inner {
+                return Some((coord, 1.0));
+            }
+            if d2 >= outer * outer {
+                continue;
+            }
+            return Some((coord, (outer - d2.sqrt()).min(1.0)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aa_shapes_left_of_or_above_the_origin_end() {
+        for origin in [(-20.0, 10.0), (10.0, -20.0), (-20.0, -20.0)] {
+            let within = |&((x, y), _): &((i32, i32), f32)| {
+                (x as f32 - origin.0).abs() <= 5.0 && (y as f32 - origin.1).abs() <= 5.0
+            };
+            let disc: Vec<_> = DiscAA::new(origin, 3.0).take(1000).collect();
+            let circle: Vec<_> = CircleAA::new(origin, 3.0).take(1000).collect();
+            assert!(!disc.is_empty() && disc.len() < 1000 && disc.iter().all(within), "disc at {origin:?}");
+            assert!(!circle.is_empty() && circle.len() < 1000 && circle.iter().all(within), "circle at {origin:?}");
+            assert_eq!(disc.len(), DiscAA::new((origin.0 + 100.0, origin.1 + 100.0), 3.0).count());
+        }
+    }
+
+    #[test]
+    fn hairline_butt_strokes_keep_both_endpoints() {
+        let style = StrokeStyle::new(1.0);
+        for (p1, p2) in [((0, 0), (10, 0)), ((0, 0), (0, -10)), ((0, 0), (10, 10)), ((3, 4), (3, 4))] {
+            let mut stroke: Vec<_> = Stroke::line(p1, p2, style).unwrap().collect();
+            let mut line: Vec<_> = Line::new(p1, p2).collect();
+            stroke.sort_unstable();
+            line.sort_unstable();
+            assert_eq!(stroke, line, "{p1:?} to {p2:?}");
+        }
+        let path: Vec<_> = Stroke::path(&[(0, 0), (5, 0), (5, 5)], style).unwrap().collect();
+        assert!(path.contains(&(0, 0)) && path.contains(&(5, 5)));
+    }
+}
@@ -0,0 +1,145 @@
+use crate::ShapeError;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum FillRule {
+    #[default]
+    EvenOdd,
+    NonZero,
+}
+
+struct Edge {
+    top: (f32, f32),
+    bottom: (f32, f32),
+    winding: i32,
+}
+
+// scanline fill of one or more closed contours, concave and self-intersecting ones included.
+// pixel centers exactly on a right or bottom edge are left out so neighbouring
+// polygons sharing an edge don't overlap
+pub struct Polygon {
+    edges: Vec<Edge>,
+    rule: FillRule,
+    row: i32,
+    last_row: i32,
+    spans: Vec<(i32, i32)>,
+    current_span: usize,
+    x: i32,
+}
+
+impl Polygon {
+    pub fn new(points: &[(i32, i32)], rule: FillRule) -> Result<Self, ShapeError> {
+        Self::from_contours(&[points.to_vec()], rule)
+    }
+
+    // every contour is closed implicitly, holes are just contours inside other contours
+    pub fn from_contours(contours: &[Vec<(i32, i32)>], rule: FillRule) -> Result<Self, ShapeError> {
+        let contours: Vec<Vec<(f32, f32)>> = contours
+            .iter()
+            .map(|c| c.iter().map(|&(x, y)| (x as f32, y as f32)).collect())
+            .collect();
+        Self::from_contours_f32(&contours, rule)
+    }
+
+    pub fn from_contours_f32(contours: &[Vec<(f32, f32)>], rule: FillRule) -> Result<Self, ShapeError> {
+        if contours.iter().all(|c| c.len() < 3) {
+            return Err(ShapeError::InvalidGeometry("polygon needs at least three points".to_string()));
+        }
+        let mut edges = vec![];
+        for contour in contours {
+            for i in 0..contour.len() {
+                let (p, q) = (contour[i], contour[(i + 1) % contour.len()]);
+                if p.1 == q.1 {
+                    continue;
+                }
+                edges.push(match p.1 < q.1 {
+                    true => Edge { top: p, bottom: q, winding: 1 },
+                    false => Edge { top: q, bottom: p, winding: -1 },
+                });
+            }
+        }
+        let y_min = edges.iter().map(|e| e.top.1).fold(f32::INFINITY, f32::min);
+        let y_max = edges.iter().map(|e| e.bottom.1).fold(f32::NEG_INFINITY, f32::max);
+        let (row, last_row) = match edges.is_empty() {
+            true => (0, -1),
+            false => (y_min.ceil() as i32, y_max.ceil() as i32 - 1),
+        };
+        Ok(Polygon {
+            edges,
+            rule,
+            row,
+            last_row,
+            spans: vec![],
+            current_span: 0,
+            x: 0,
+        })
+    }
+
+    fn row_spans(&self, y: i32) -> Vec<(i32, i32)> {
+        let yf = y as f32;
+        let mut crossings: Vec<(f32, i32)> = self
+            .edges
+            .iter()
+            .filter(|e| e.top.1 <= yf && yf < e.bottom.1)
+            .map(|e| {
+                let t = (yf - e.top.1) / (e.bottom.1 - e.top.1);
+                (e.top.0 + t * (e.bottom.0 - e.top.0), e.winding)
+            })
+            .collect();
+        crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut spans = vec![];
+        let (mut winding, mut count) = (0, 0);
+        let mut start = 0.0;
+        for (x, w) in crossings {
+            let was_inside = self.inside(winding, count);
+            winding += w;
+            count += 1;
+            match (was_inside, self.inside(winding, count)) {
+                (false, true) => start = x,
+                (true, false) => {
+                    let (x1, x2) = (start.ceil() as i32, x.ceil() as i32 - 1);
+                    if x1 <= x2 {
+                        spans.push((x1, x2));
+                    }
+                }
+                _ => {}
+            }
+        }
+        spans
+    }
+
+    fn inside(&self, winding: i32, count: i32) -> bool {
+        match self.rule {
+            FillRule::EvenOdd => count % 2 == 1,
+            FillRule::NonZero => winding != 0,
+        }
+    }
+}
+
+impl Iterator for Polygon {
+    type Item = (i32, i32);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(&(_, x2)) = self.spans.get(self.current_span) {
+                let p = (self.x, self.row - 1);
+                if self.x == x2 {
+                    self.current_span += 1;
+                    if let Some(&(x1, _)) = self.spans.get(self.current_span) {
+                        self.x = x1;
+                    }
+                } else {
+                    self.x += 1;
+                }
+                return Some(p);
+            }
+            if self.row > self.last_row {
+                return None;
+            }
+            self.spans = self.row_spans(self.row);
+            self.current_span = 0;
+            self.x = self.spans.first().map_or(0, |s| s.0);
+            self.row += 1;
+        }
+    }
+}
@@ -0,0 +1,270 @@
+use crate::ShapeError;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LineCap {
+    Butt,
+    Round,
+    Square,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LineJoin {
+    Miter,
+    Round,
+    Bevel,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StrokeStyle {
+    pub width: f32,
+    pub cap: LineCap,
+    pub join: LineJoin,
+    // max ratio of miter length to the full stroke width before a miter falls back to a bevel, like svg
+    pub miter_limit: f32,
+}
+
+impl StrokeStyle {
+    pub fn new(width: f32) -> Self {
+        StrokeStyle {
+            width,
+            cap: LineCap::Butt,
+            join: LineJoin::Miter,
+            miter_limit: 4.0,
+        }
+    }
+    pub fn with_cap(mut self, cap: LineCap) -> Self {
+        self.cap = cap;
+        self
+    }
+    pub fn with_join(mut self, join: LineJoin) -> Self {
+        self.join = join;
+        self
+    }
+    pub fn with_miter_limit(mut self, miter_limit: f32) -> Self {
+        self.miter_limit = miter_limit;
+        self
+    }
+}
+
+// every pixel covered by a thick line, path or circuit, each pixel yielded once.
+// the outline is split into convex pieces (segment bodies, caps, joins) whose
+// row spans get merged up front
+pub struct Stroke {
+    spans: Vec<(i32, i32, i32)>,
+    current_span: usize,
+    x: i32,
+}
+
+enum Piece {
+    Convex(Vec<(f32, f32)>),
+    Disc((f32, f32), f32),
+}
+
+impl Stroke {
+    pub fn line(p1: (i32, i32), p2: (i32, i32), style: StrokeStyle) -> Result<Self, ShapeError> {
+        Self::path(&[p1, p2], style)
+    }
+    pub fn path(points: &[(i32, i32)], style: StrokeStyle) -> Result<Self, ShapeError> {
+        Self::new(points, false, style)
+    }
+    pub fn circuit(points: &[(i32, i32)], style: StrokeStyle) -> Result<Self, ShapeError> {
+        Self::new(points, true, style)
+    }
+
+    fn new(points: &[(i32, i32)], closed: bool, style: StrokeStyle) -> Result<Self, ShapeError> {
+        if points.is_empty() {
+            return Err(ShapeError::InvalidGeometry("stroke needs at least one point".to_string()));
+        }
+        if !(style.width.is_finite() && style.width > 0.0) {
+            return Err(ShapeError::InvalidGeometry(format!("stroke width {} is not positive", style.width)));
+        }
+        let mut pts: Vec<(f32, f32)> = points.iter().map(|&(x, y)| (x as f32, y as f32)).collect();
+        pts.dedup();
+        if closed && pts.len() > 1 && pts[0] == pts[pts.len() - 1] {
+            pts.pop();
+        }
+        let pieces = pieces(&pts, closed && pts.len() > 2, style);
+
+        let mut spans = vec![];
+        for piece in &pieces {
+            piece_spans(piece, &mut spans);
+        }
+        spans.sort_unstable();
+        let mut merged: Vec<(i32, i32, i32)> = vec![];
+        for (y, x1, x2) in spans {
+            match merged.last_mut() {
+                Some(last) if last.0 == y && x1 <= last.2 + 1 => last.2 = last.2.max(x2),
+                _ => merged.push((y, x1, x2)),
+            }
+        }
+        let x = merged.first().map_or(0, |s| s.1);
+        Ok(Stroke { spans: merged, current_span: 0, x })
+    }
+}
+
+impl Iterator for Stroke {
+    type Item = (i32, i32);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let &(y, _, x2) = self.spans.get(self.current_span)?;
+        let p = (self.x, y);
+        if self.x == x2 {
+            self.current_span += 1;
+            if let Some(&(_, x1, _)) = self.spans.get(self.current_span) {
+                self.x = x1;
+            }
+        } else {
+            self.x += 1;
+        }
+        Some(p)
+    }
+}
+
+fn pieces(pts: &[(f32, f32)], closed: bool, style: StrokeStyle) -> Vec<Piece> {
+    let hw = style.width / 2.0;
+    // spans are half-open, so a butt end at a pixel center would drop that pixel.
+    // hairlines push their butt ends out by half a pixel to keep both endpoints, like draw_line
+    let cap_ext = match style.cap {
+        LineCap::Square => hw,
+        LineCap::Butt if style.width <= 1.0 => 0.5,
+        _ => 0.0,
+    };
+    let mut pieces = vec![];
+
+    if pts.len() == 1 {
+        let (x, y) = pts[0];
+        match style.cap {
+            LineCap::Butt if cap_ext > 0.0 => pieces.push(Piece::Convex(vec![
+                (x - cap_ext, y - hw),
+                (x + cap_ext, y - hw),
+                (x + cap_ext, y + hw),
+                (x - cap_ext, y + hw),
+            ])),
+            LineCap::Butt => {}
+            LineCap::Round => pieces.push(Piece::Disc(pts[0], hw)),
+            LineCap::Square => pieces.push(Piece::Convex(vec![
+                (x - hw, y - hw),
+                (x + hw, y - hw),
+                (x + hw, y + hw),
+                (x - hw, y + hw),
+            ])),
+        }
+        return pieces;
+    }
+
+    let n_segments = if closed { pts.len() } else { pts.len() - 1 };
+    for i in 0..n_segments {
+        let a = pts[i];
+        let b = pts[(i + 1) % pts.len()];
+        let (d, n) = direction(a, b);
+        let start_cap = !closed && i == 0;
+        let end_cap = !closed && i == n_segments - 1;
+        let ext_start = if start_cap { cap_ext } else { 0.0 };
+        let ext_end = if end_cap { cap_ext } else { 0.0 };
+        let a = (a.0 - d.0 * ext_start, a.1 - d.1 * ext_start);
+        let b = (b.0 + d.0 * ext_end, b.1 + d.1 * ext_end);
+        pieces.push(Piece::Convex(vec![
+            (a.0 + n.0 * hw, a.1 + n.1 * hw),
+            (b.0 + n.0 * hw, b.1 + n.1 * hw),
+            (b.0 - n.0 * hw, b.1 - n.1 * hw),
+            (a.0 - n.0 * hw, a.1 - n.1 * hw),
+        ]));
+        if style.cap == LineCap::Round {
+            if start_cap {
+                pieces.push(Piece::Disc(pts[0], hw));
+            }
+            if end_cap {
+                pieces.push(Piece::Disc(pts[pts.len() - 1], hw));
+            }
+        }
+    }
+
+    let joins = if closed { 0..pts.len() } else { 1..pts.len() - 1 };
+    for i in joins {
+        let prev = pts[(i + pts.len() - 1) % pts.len()];
+        let v = pts[i];
+        let next = pts[(i + 1) % pts.len()];
+        if let Some(piece) = join(prev, v, next, hw, style) {
+            pieces.push(piece);
+        }
+    }
+    pieces
+}
+
+fn direction(a: (f32, f32), b: (f32, f32)) -> ((f32, f32), (f32, f32)) {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let len = (dx * dx + dy * dy).sqrt();
+    let d = (dx / len, dy / len);
+    (d, (-d.1, d.0))
+}
+
+fn join(prev: (f32, f32), v: (f32, f32), next: (f32, f32), hw: f32, style: StrokeStyle) -> Option<Piece> {
+    if style.join == LineJoin::Round {
+        return Some(Piece::Disc(v, hw));
+    }
+    let (d1, n1) = direction(prev, v);
+    let (d2, n2) = direction(v, next);
+    let cross = d1.0 * d2.1 - d1.1 * d2.0;
+    let dot = d1.0 * d2.0 + d1.1 * d2.1;
+    if cross.abs() < 1e-6 && dot > 0.0 {
+        return None;
+    }
+    // the gap to fill is on the outside of the turn
+    let s = if cross > 0.0 { -hw } else { hw };
+    let o1 = (v.0 + n1.0 * s, v.1 + n1.1 * s);
+    let o2 = (v.0 + n2.0 * s, v.1 + n2.1 * s);
+
+    if style.join == LineJoin::Miter {
+        let (bx, by) = (n1.0 + n2.0, n1.1 + n2.1);
+        let half_len = (bx * bx + by * by).sqrt() / 2.0;
+        if half_len > 1e-6 && 1.0 / half_len <= style.miter_limit {
+            let scale = s / (2.0 * half_len * half_len);
+            let tip = (v.0 + bx * scale, v.1 + by * scale);
+            return Some(Piece::Convex(vec![v, o1, tip, o2]));
+        }
+    }
+    Some(Piece::Convex(vec![v, o1, o2]))
+}
+
+// pixel centers inside a convex piece, as (row, first x, last x). edges are half-open
+// (top-left rule) so a stroke of width w covers exactly w rows or columns
+fn piece_spans(piece: &Piece, spans: &mut Vec<(i32, i32, i32)>) {
+    match piece {
+        Piece::Disc((cx, cy), r) => {
+            for y in (cy - r).ceil() as i32..(cy + r).ceil() as i32 {
+                let dy = y as f32 - cy;
+                let dx = (r * r - dy * dy).max(0.0).sqrt();
+                let (x1, x2) = ((cx - dx).ceil() as i32, (cx + dx).ceil() as i32 - 1);
+                if x1 <= x2 {
+                    spans.push((y, x1, x2));
+                }
+            }
+        }
+        Piece::Convex(poly) => {
+            let y_min = poly.iter().map(|p| p.1).fold(f32::INFINITY, f32::min);
+            let y_max = poly.iter().map(|p| p.1).fold(f32::NEG_INFINITY, f32::max);
+            for y in y_min.ceil() as i32..y_max.ceil() as i32 {
+                let yf = y as f32;
+                let (mut lo, mut hi) = (f32::INFINITY, f32::NEG_INFINITY);
+                for i in 0..poly.len() {
+                    let (p, q) = (poly[i], poly[(i + 1) % poly.len()]);
+                    if (p.1 - yf) * (q.1 - yf) > 0.0 {
+                        continue;
+                    }
+                    if p.1 == q.1 {
+                        lo = lo.min(p.0.min(q.0));
+                        hi = hi.max(p.0.max(q.0));
+                    } else {
+                        let x = p.0 + (yf - p.1) * (q.0 - p.0) / (q.1 - p.1);
+                        lo = lo.min(x);
+                        hi = hi.max(x);
+                    }
+                }
+                let (x1, x2) = (lo.ceil() as i32, hi.ceil() as i32 - 1);
+                if x1 <= x2 {
+                    spans.push((y, x1, x2));
+                }
+            }
+        }
+    }
+}
@@ -1,38 +1,13 @@
 pub use limage_core::*;
 pub use shiterators::*;
+pub use limage_core::css::*;
 
 // range (360 1 1)
 pub fn hsl_to_rgb(hsl: [f32; 3]) -> [u8; 3] {
-    let h = hsl[0] % 360.0;
-    let s = hsl[1];
-    let l = hsl[2];
-
-    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
-    let x = c * (1.0 - ((h / 60.0) % 2.0 - 1.0).abs());
-    let m = l - c / 2.0;
-
-    let rgb_tmp = match h {
-        _ if h < 60.0 => (c, x, 0.0),
-        _ if h < 120.0 => (x, c, 0.0),
-        _ if h < 180.0 => (0.0, c, x),
-        _ if h < 240.0 => (0.0, x, c),
-        _ if h < 300.0 => (x, 0.0, c),
-        _ => (c, 0.0, x),
-    };
-
-    [
-        ((rgb_tmp.0 + m) * 256.) as u8,
-        ((rgb_tmp.1 + m) * 256.) as u8,
-        ((rgb_tmp.2 + m) * 256.) as u8,
-    ]
+    Color::from_hsl(hsl[0], hsl[1], hsl[2]).to_rgb()
 }
 
-pub const RED: [u8; 3] = [255, 0, 0];
+// these two predate the css table and keep their old values, css green is darker (see `LIME`)
+// and css beige is paler (this one is `BURLYWOOD`). the css ones live in `css::GREEN` and `css::BEIGE`
 pub const GREEN: [u8; 3] = [0, 255, 0];
-pub const BLUE: [u8; 3] = [0, 0, 255];
-pub const YELLOW: [u8; 3] = [255, 255, 0];
-pub const MAGENTA: [u8; 3] = [255, 0, 255];
-pub const CYAN: [u8; 3] = [0, 255, 255];
-
 pub const BEIGE: [u8; 3] = [222, 184, 135];
-pub const FOREST_GREEN: [u8; 3] = [34, 139, 34];